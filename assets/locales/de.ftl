@@ -0,0 +1,71 @@
+language-name = Deutsch
+
+app-title = Flappy Bird
+
+loading-progress = Lade... { $loaded }/{ $total }
+loading-failed = Laden fehlgeschlagen...
+loading-error =
+    Konnte nicht geladen werden: { $assets }
+    Drücke <R>, um es erneut zu versuchen
+
+menu-play = Spielen
+menu-settings = Einstellungen
+menu-high-scores = Bestenliste
+menu-stats = Statistik
+menu-quit = Beenden
+back = Zurück
+
+settings-title = Einstellungen
+settings-theme = Thema: { $theme }
+settings-scaling = Skalierung: { $mode }
+settings-language = Sprache: { $language }
+settings-unlock = Erreiche { $score } Punkte, um das nächste Thema freizuschalten
+settings-all-unlocked = Alle Themen sind freigeschaltet
+
+theme-day = Tag
+theme-night = Nacht
+
+scaling-letterbox = Letterbox
+scaling-fit = Einpassen
+scaling-stretch = Strecken
+
+high-scores-title = Bestenliste
+high-scores-empty = Noch keine Punkte
+high-scores-entry = { $rank }. { $score }
+
+stats-title = Statistik
+stats-games = Gespielte Spiele: { $count }
+stats-pillars = Passierte Säulen: { $count }
+stats-flaps = Flügelschläge: { $count }
+stats-average = Durchschnittliche Punkte: { $score }
+stats-median = Median der Punkte: { $score }
+stats-longest-session = Längste Sitzung: { $time }
+stats-deaths-top-pillar = Obere Säule getroffen: { $count }
+stats-deaths-bottom-pillar = Untere Säule getroffen: { $count }
+stats-deaths-ceiling = Oben hinausgeflogen: { $count }
+stats-deaths-floor = Auf den Boden gefallen: { $count }
+
+get-ready-title = Mach dich bereit!
+get-ready-countdown = { $seconds }
+get-ready-hint = Drücke <Leertaste>, um zwischen den Säulen durchzufliegen
+
+attract-title = Demo
+attract-hint = Drücke eine beliebige Taste, um zu spielen
+
+ghost-score = Geist: { $score }
+
+game-over-title = Spiel vorbei!
+game-over-score = Punkte: { $score }
+game-over-best = Rekord: { $best }
+game-over-new-best = NEUER REKORD
+game-over-restart = Neustart
+game-over-main-menu = Hauptmenü
+death-top-pillar = Nach { $time } s an eine obere Säule gestoßen
+death-bottom-pillar = Nach { $time } s an eine untere Säule gestoßen
+death-ceiling = Nach { $time } s oben hinausgeflogen
+death-floor = Nach { $time } s auf den Boden gefallen
+
+medal-bronze = Bronzemedaille
+medal-silver = Silbermedaille
+medal-gold = Goldmedaille
+medal-platinum = Platinmedaille
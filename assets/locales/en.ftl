@@ -0,0 +1,75 @@
+# English strings, also used for anything another language is missing.
+# A message continues on the following indented lines, each one a new line of text.
+# { $name } is replaced with the value the game passes for it.
+
+language-name = English
+
+app-title = Flappy Bird
+
+loading-progress = Loading... { $loaded }/{ $total }
+loading-failed = Loading failed...
+loading-error =
+    Could not load: { $assets }
+    Press <R> to retry
+
+menu-play = Play
+menu-settings = Settings
+menu-high-scores = High Scores
+menu-stats = Statistics
+menu-quit = Quit
+back = Back
+
+settings-title = Settings
+settings-theme = Theme: { $theme }
+settings-scaling = Scaling: { $mode }
+settings-language = Language: { $language }
+settings-unlock = Score { $score } points to unlock the next theme
+settings-all-unlocked = Every theme is unlocked
+
+theme-day = Day
+theme-night = Night
+
+scaling-letterbox = Letterbox
+scaling-fit = Fit
+scaling-stretch = Stretch
+
+high-scores-title = High Scores
+high-scores-empty = No scores yet
+high-scores-entry = { $rank }. { $score }
+
+stats-title = Statistics
+stats-games = Games played: { $count }
+stats-pillars = Pillars crossed: { $count }
+stats-flaps = Flaps: { $count }
+stats-average = Average score: { $score }
+stats-median = Median score: { $score }
+stats-longest-session = Longest session: { $time }
+stats-deaths-top-pillar = Hit a top pillar: { $count }
+stats-deaths-bottom-pillar = Hit a bottom pillar: { $count }
+stats-deaths-ceiling = Flew off the top: { $count }
+stats-deaths-floor = Fell to the ground: { $count }
+
+get-ready-title = Get Ready!
+get-ready-countdown = { $seconds }
+get-ready-hint = Press <Space> to flap past the pillars
+
+attract-title = Demo
+attract-hint = Press any key to play
+
+ghost-score = Ghost: { $score }
+
+game-over-title = Game Over!
+game-over-score = Score: { $score }
+game-over-best = Best: { $best }
+game-over-new-best = NEW BEST
+game-over-restart = Restart
+game-over-main-menu = Main Menu
+death-top-pillar = Hit a top pillar after { $time } s
+death-bottom-pillar = Hit a bottom pillar after { $time } s
+death-ceiling = Flew off the top after { $time } s
+death-floor = Fell to the ground after { $time } s
+
+medal-bronze = Bronze Medal
+medal-silver = Silver Medal
+medal-gold = Gold Medal
+medal-platinum = Platinum Medal
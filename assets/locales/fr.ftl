@@ -0,0 +1,71 @@
+language-name = Français
+
+app-title = Flappy Bird
+
+loading-progress = Chargement... { $loaded }/{ $total }
+loading-failed = Échec du chargement...
+loading-error =
+    Impossible de charger : { $assets }
+    Appuyez sur <R> pour réessayer
+
+menu-play = Jouer
+menu-settings = Options
+menu-high-scores = Meilleurs scores
+menu-stats = Statistiques
+menu-quit = Quitter
+back = Retour
+
+settings-title = Options
+settings-theme = Thème : { $theme }
+settings-scaling = Mise à l'échelle : { $mode }
+settings-language = Langue : { $language }
+settings-unlock = Marquez { $score } points pour débloquer le thème suivant
+settings-all-unlocked = Tous les thèmes sont débloqués
+
+theme-day = Jour
+theme-night = Nuit
+
+scaling-letterbox = Bandes noires
+scaling-fit = Ajuster
+scaling-stretch = Étirer
+
+high-scores-title = Meilleurs scores
+high-scores-empty = Aucun score pour l'instant
+high-scores-entry = { $rank }. { $score }
+
+stats-title = Statistiques
+stats-games = Parties jouées : { $count }
+stats-pillars = Piliers franchis : { $count }
+stats-flaps = Battements d'ailes : { $count }
+stats-average = Score moyen : { $score }
+stats-median = Score médian : { $score }
+stats-longest-session = Plus longue session : { $time }
+stats-deaths-top-pillar = Pilier du haut heurté : { $count }
+stats-deaths-bottom-pillar = Pilier du bas heurté : { $count }
+stats-deaths-ceiling = Sorti par le haut : { $count }
+stats-deaths-floor = Tombé au sol : { $count }
+
+get-ready-title = Préparez-vous !
+get-ready-countdown = { $seconds }
+get-ready-hint = Appuyez sur <Espace> pour voler entre les piliers
+
+attract-title = Démo
+attract-hint = Appuyez sur une touche pour jouer
+
+ghost-score = Fantôme : { $score }
+
+game-over-title = Partie terminée !
+game-over-score = Score : { $score }
+game-over-best = Record : { $best }
+game-over-new-best = NOUVEAU RECORD
+game-over-restart = Rejouer
+game-over-main-menu = Menu principal
+death-top-pillar = Pilier du haut heurté après { $time } s
+death-bottom-pillar = Pilier du bas heurté après { $time } s
+death-ceiling = Sorti par le haut après { $time } s
+death-floor = Tombé au sol après { $time } s
+
+medal-bronze = Médaille de bronze
+medal-silver = Médaille d'argent
+medal-gold = Médaille d'or
+medal-platinum = Médaille de platine
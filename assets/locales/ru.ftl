@@ -0,0 +1,71 @@
+language-name = Русский
+
+app-title = Flappy Bird
+
+loading-progress = Загрузка... { $loaded }/{ $total }
+loading-failed = Ошибка загрузки...
+loading-error =
+    Не удалось загрузить: { $assets }
+    Нажмите <R>, чтобы повторить
+
+menu-play = Играть
+menu-settings = Настройки
+menu-high-scores = Рекорды
+menu-stats = Статистика
+menu-quit = Выход
+back = Назад
+
+settings-title = Настройки
+settings-theme = Тема: { $theme }
+settings-scaling = Масштаб: { $mode }
+settings-language = Язык: { $language }
+settings-unlock = Наберите { $score } очков, чтобы открыть следующую тему
+settings-all-unlocked = Все темы открыты
+
+theme-day = День
+theme-night = Ночь
+
+scaling-letterbox = С полосами
+scaling-fit = Вписать
+scaling-stretch = Растянуть
+
+high-scores-title = Рекорды
+high-scores-empty = Рекордов пока нет
+high-scores-entry = { $rank }. { $score }
+
+stats-title = Статистика
+stats-games = Сыграно игр: { $count }
+stats-pillars = Пройдено колонн: { $count }
+stats-flaps = Взмахов: { $count }
+stats-average = Средний счёт: { $score }
+stats-median = Медианный счёт: { $score }
+stats-longest-session = Самая долгая сессия: { $time }
+stats-deaths-top-pillar = Удар о верхнюю колонну: { $count }
+stats-deaths-bottom-pillar = Удар о нижнюю колонну: { $count }
+stats-deaths-ceiling = Вылет за верх: { $count }
+stats-deaths-floor = Падение на землю: { $count }
+
+get-ready-title = Приготовьтесь!
+get-ready-countdown = { $seconds }
+get-ready-hint = Нажмите <Пробел>, чтобы пролететь между колоннами
+
+attract-title = Демо
+attract-hint = Нажмите любую клавишу, чтобы играть
+
+ghost-score = Призрак: { $score }
+
+game-over-title = Игра окончена!
+game-over-score = Очки: { $score }
+game-over-best = Рекорд: { $best }
+game-over-new-best = НОВЫЙ РЕКОРД
+game-over-restart = Заново
+game-over-main-menu = Главное меню
+death-top-pillar = Удар о верхнюю колонну через { $time } с
+death-bottom-pillar = Удар о нижнюю колонну через { $time } с
+death-ceiling = Вылет за верх через { $time } с
+death-floor = Падение на землю через { $time } с
+
+medal-bronze = Бронзовая медаль
+medal-silver = Серебряная медаль
+medal-gold = Золотая медаль
+medal-platinum = Платиновая медаль
@@ -0,0 +1,189 @@
+use bevy::prelude::*;
+
+use crate::{
+    autopilot::{Autopilot, AutopilotStrategy},
+    game_core::{GameClock, GameSystem},
+    game_state::{GameState, GameStateType, OpenMenuEvent, StartNewGameEvent},
+    player::PlayerControl,
+    replay::{Replay, ReplayMode},
+    widgets::{self, UiTheme},
+};
+
+/// Plays a demo run once the start screen has been left alone for a while, until any key,
+/// button or click brings the menu back.
+pub struct AttractPlugin;
+
+impl Plugin for AttractPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<AttractMode>()
+            .insert_resource(AttractState::default())
+            .add_system_set(GameSystem::Input.set().with_system(attract_system))
+            .add_system_set(GameSystem::Presentation.set().with_system(overlay_system));
+    }
+}
+
+/// Inserted before the plugin to choose the demo, or changed at any time.
+pub struct AttractMode {
+    /// seconds the start screen sits idle before the demo starts, or `None` to never start it
+    pub idle_seconds: Option<f32>,
+    pub demo: AttractDemo,
+}
+
+impl Default for AttractMode {
+    fn default() -> Self {
+        Self {
+            idle_seconds: Some(20.0),
+            demo: AttractDemo::Autopilot(AutopilotStrategy::Lookahead),
+        }
+    }
+}
+
+pub enum AttractDemo {
+    Autopilot(AutopilotStrategy),
+    Replay(Replay),
+}
+
+/// Seconds the game over screen of the demo is shown before going back to the menu.
+const DEMO_GAME_OVER_SECONDS: f32 = 2.0;
+
+#[derive(Default)]
+struct AttractState {
+    running: bool,
+    /// seconds the start screen has been idle, or the game over screen of the demo shown
+    elapsed: f32,
+    /// the replay mode the demo replaced, put back once it is over
+    replaced_replay_mode: Option<ReplayMode>,
+}
+
+#[derive(Component)]
+struct AttractOverlay;
+
+fn attract_system(
+    mut state: ResMut<AttractState>,
+    attract: Res<AttractMode>,
+    time: Res<GameClock>,
+    game_state: Res<GameState>,
+    keyboard_input: Res<Input<KeyCode>>,
+    mouse_input: Res<Input<MouseButton>>,
+    gamepad_input: Res<Input<GamepadButton>>,
+    mut control: ResMut<PlayerControl>,
+    mut autopilot: ResMut<Autopilot>,
+    mut replay_mode: ResMut<ReplayMode>,
+    mut start_new_events: EventWriter<StartNewGameEvent>,
+    mut open_menu_events: EventWriter<OpenMenuEvent>,
+) {
+    let any_input = keyboard_input.get_just_pressed().next().is_some()
+        || mouse_input.get_just_pressed().next().is_some()
+        || gamepad_input.get_just_pressed().next().is_some();
+
+    if !state.running {
+        // nothing to show while something else is playing
+        let idle_seconds = match attract.idle_seconds {
+            Some(seconds) if game_state.0 == GameStateType::StartScreen && !control.scripted => {
+                seconds
+            }
+            _ => {
+                state.elapsed = 0.0;
+                return;
+            }
+        };
+
+        state.elapsed = if any_input {
+            0.0
+        } else {
+            state.elapsed + time.delta_seconds()
+        };
+
+        if state.elapsed >= idle_seconds {
+            match &attract.demo {
+                AttractDemo::Autopilot(strategy) => {
+                    // the demo run is not the player's to record
+                    state.replaced_replay_mode =
+                        Some(std::mem::replace(&mut *replay_mode, ReplayMode::Off));
+                    *autopilot = Autopilot {
+                        enabled: true,
+                        strategy: *strategy,
+                        restart: false,
+                    };
+                    start_new_events.send(StartNewGameEvent);
+                }
+                // the replay starts the run itself
+                AttractDemo::Replay(replay) => {
+                    state.replaced_replay_mode = Some(std::mem::replace(
+                        &mut *replay_mode,
+                        ReplayMode::Play {
+                            replay: replay.clone(),
+                            exit_when_done: false,
+                        },
+                    ));
+                }
+            }
+
+            state.running = true;
+            state.elapsed = 0.0;
+        }
+        return;
+    }
+
+    let game_over = game_state.0 == GameStateType::GameOver;
+    if game_over {
+        state.elapsed += time.delta_seconds();
+
+        // a replay hands the controls back once it is over, but the game over screen is
+        // still part of the demo
+        control.scripted = true;
+        control.tap = false;
+    }
+
+    if any_input || (game_over && state.elapsed >= DEMO_GAME_OVER_SECONDS) {
+        if let AttractDemo::Autopilot(_) = &attract.demo {
+            autopilot.enabled = false;
+        }
+        *replay_mode = state.replaced_replay_mode.take().unwrap_or_default();
+        control.scripted = false;
+        control.tap = false;
+        open_menu_events.send(OpenMenuEvent(GameStateType::StartScreen));
+
+        state.running = false;
+        state.elapsed = 0.0;
+    }
+}
+
+fn overlay_system(
+    mut commands: Commands,
+    ui_theme: Res<UiTheme>,
+    state: Res<AttractState>,
+    query: Query<Entity, With<AttractOverlay>>,
+) {
+    let shown = query.iter().next().is_some();
+    if state.running == shown {
+        return;
+    }
+
+    query
+        .iter()
+        .for_each(|entity| commands.entity(entity).despawn_recursive());
+
+    if state.running {
+        commands
+            .spawn_bundle(NodeBundle {
+                style: Style {
+                    position_type: PositionType::Absolute,
+                    size: Size::new(Val::Percent(100.0), Val::Percent(100.0)),
+                    // at the bottom, clear of the get ready and game over screens
+                    flex_direction: FlexDirection::ColumnReverse,
+                    justify_content: JustifyContent::FlexEnd,
+                    align_items: AlignItems::Center,
+                    padding: Rect::all(Val::Px(24.0)),
+                    ..Default::default()
+                },
+                color: UiColor(Color::NONE),
+                ..Default::default()
+            })
+            .insert(AttractOverlay)
+            .with_children(|parent| {
+                widgets::title(parent, &ui_theme, "attract-title");
+                widgets::label(parent, &ui_theme, "attract-hint");
+            });
+    }
+}
@@ -1,50 +1,200 @@
+use std::collections::HashMap;
+
 use crate::{
-    loading::LoadingAssets,
+    game_assets::GameAssets,
+    game_core::{GameClock, GameSystem},
     player::{PlayerCrossedPillarEvent, PlayerKilledEvent},
 };
 use bevy::prelude::*;
-use bevy_kira_audio::{Audio, AudioPlugin, AudioSource};
+use bevy_kira_audio::{Audio, AudioSource, InstanceHandle, PlaybackState};
 
+/// Plays the cues of the game. Needs `bevy_kira_audio::AudioPlugin`, which `FlappyGamePlugins`
+/// adds unless the app brings its own.
 pub struct GameAudioPlugin;
 
 impl Plugin for GameAudioPlugin {
     fn build(&self, app: &mut App) {
-        app.add_plugin(AudioPlugin)
+        app.init_resource::<AudioSettings>()
+            .insert_resource(AudioQueue::default())
+            .add_event::<PlayAudioCueEvent>()
             .add_startup_system(setup_audio)
-            .add_system(audio_system);
+            .add_system_set(
+                GameSystem::Presentation
+                    .set()
+                    .with_system(audio_event_system.label(AudioSystem::Queue))
+                    .with_system(audio_playback_system.after(AudioSystem::Queue)),
+            );
+    }
+}
+
+#[derive(SystemLabel, Debug, Clone, PartialEq, Eq, Hash)]
+enum AudioSystem {
+    Queue,
+}
+
+/// Inserted before the plugins to start muted.
+#[derive(Default)]
+pub struct AudioSettings {
+    /// cues are dropped instead of played
+    pub muted: bool,
+}
+
+/// Maximum number of cues (of any kind) that can be heard at once.
+const MAX_ACTIVE_CUES: usize = 6;
+
+#[derive(Clone, Copy, PartialEq, Eq, Hash, Debug)]
+pub enum AudioCue {
+    Flap,
+    Crossed,
+    Dead,
+}
+
+impl AudioCue {
+    fn settings(&self) -> CueSettings {
+        match self {
+            AudioCue::Flap => CueSettings {
+                polyphony: 2,
+                cooldown: 0.05,
+                priority: 0,
+                max_delay: 0.1,
+            },
+            AudioCue::Crossed => CueSettings {
+                polyphony: 3,
+                cooldown: 0.08,
+                priority: 1,
+                max_delay: 0.5,
+            },
+            AudioCue::Dead => CueSettings {
+                polyphony: 1,
+                cooldown: 0.5,
+                priority: 2,
+                max_delay: 0.5,
+            },
+        }
     }
 }
 
+struct CueSettings {
+    /// how many instances of this cue may play at the same time
+    polyphony: usize,
+    /// minimum seconds between two instances of this cue
+    cooldown: f64,
+    /// higher priority cues are played first when voices are scarce
+    priority: u8,
+    /// cues that could not be played within this many seconds are dropped
+    max_delay: f64,
+}
+
+pub struct PlayAudioCueEvent(pub AudioCue);
+
+struct QueuedCue {
+    cue: AudioCue,
+    queued_at: f64,
+}
+
+#[derive(Default)]
+struct AudioQueue {
+    pending: Vec<QueuedCue>,
+    active: HashMap<AudioCue, Vec<InstanceHandle>>,
+    last_played: HashMap<AudioCue, f64>,
+}
+
 struct AudioCollection {
+    flap: Handle<AudioSource>,
     crossed: Handle<AudioSource>,
     dead: Handle<AudioSource>,
 }
 
-fn setup_audio(
-    mut commands: Commands,
-    asset_server: Res<AssetServer>,
-    mut loading: ResMut<LoadingAssets>,
+impl AudioCollection {
+    fn get(&self, cue: AudioCue) -> Handle<AudioSource> {
+        match cue {
+            AudioCue::Flap => self.flap.clone(),
+            AudioCue::Crossed => self.crossed.clone(),
+            AudioCue::Dead => self.dead.clone(),
+        }
+    }
+}
+
+fn setup_audio(mut commands: Commands, game_assets: Res<GameAssets>) {
+    commands.insert_resource(AudioCollection {
+        flap: game_assets.sound("flap"),
+        crossed: game_assets.sound("crossed"),
+        dead: game_assets.sound("dead"),
+    });
+}
+
+fn audio_event_system(
+    time: Res<GameClock>,
+    settings: Res<AudioSettings>,
+    mut queue: ResMut<AudioQueue>,
+    mut cue_events: EventReader<PlayAudioCueEvent>,
+    mut crossed_events: EventReader<PlayerCrossedPillarEvent>,
+    mut killed_events: EventReader<PlayerKilledEvent>,
 ) {
-    let crossed = asset_server.load("crossed.wav");
-    let dead = asset_server.load("dead.wav");
+    let now = time.seconds_since_startup();
 
-    loading.0.push(crossed.clone_untyped());
-    loading.0.push(dead.clone_untyped());
+    let cues: Vec<AudioCue> = cue_events
+        .iter()
+        .map(|event| event.0)
+        .chain(crossed_events.iter().map(|_| AudioCue::Crossed))
+        .chain(killed_events.iter().map(|_| AudioCue::Dead))
+        .collect();
+
+    // read either way, so unmuting does not play the cues of the muted frames
+    if settings.muted {
+        return;
+    }
 
-    commands.insert_resource(AudioCollection { crossed, dead });
+    queue.pending.extend(cues.into_iter().map(|cue| QueuedCue {
+        cue,
+        queued_at: now,
+    }));
 }
 
-fn audio_system(
+fn audio_playback_system(
+    time: Res<GameClock>,
     audio: Res<Audio>,
     audio_collection: Res<AudioCollection>,
-    mut crossed_events: EventReader<PlayerCrossedPillarEvent>,
-    mut killed_events: EventReader<PlayerKilledEvent>,
+    mut queue: ResMut<AudioQueue>,
 ) {
-    if crossed_events.iter().count() > 0 {
-        audio.play(audio_collection.crossed.clone());
-    };
+    let now = time.seconds_since_startup();
+    let queue = &mut *queue;
+
+    queue.active.values_mut().for_each(|instances| {
+        instances
+            .retain(|instance| !matches!(audio.state(instance.clone()), PlaybackState::Stopped));
+    });
+
+    queue
+        .pending
+        .retain(|queued| now - queued.queued_at <= queued.cue.settings().max_delay);
+
+    // stable sort, so cues of the same priority keep the order they were raised in
+    queue
+        .pending
+        .sort_by_key(|queued| std::cmp::Reverse(queued.cue.settings().priority));
 
-    if killed_events.iter().count() > 0 {
-        audio.play(audio_collection.dead.clone());
+    let mut total_active: usize = queue.active.values().map(|instances| instances.len()).sum();
+    let mut deferred = vec![];
+
+    for queued in queue.pending.drain(..) {
+        let settings = queued.cue.settings();
+        let instances = queue.active.entry(queued.cue).or_default();
+        let cooling_down = queue
+            .last_played
+            .get(&queued.cue)
+            .map_or(false, |last| now - last < settings.cooldown);
+
+        if total_active >= MAX_ACTIVE_CUES || instances.len() >= settings.polyphony || cooling_down
+        {
+            deferred.push(queued);
+            continue;
+        }
+
+        instances.push(audio.play(audio_collection.get(queued.cue)));
+        queue.last_played.insert(queued.cue, now);
+        total_active += 1;
     }
+
+    queue.pending = deferred;
 }
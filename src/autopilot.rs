@@ -0,0 +1,244 @@
+use bevy::prelude::*;
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    game_core::{GameClock, Playfield, Tuning},
+    game_state::{GameState, GameStateType, StartNewGameEvent},
+    mover::Mover,
+    pillars::{pillar_hit, Difficulty, Pillar, PILLAR_WIDTH},
+    player::{Player, PlayerControl, PlayerKilledEvent, LEAP_Y_VELOCITY},
+    score::Score,
+};
+
+/// Takes over the controls while `Autopilot::enabled` is set, flapping the bird through the
+/// pillars instead of the player.
+pub struct AutopilotPlugin;
+
+impl Plugin for AutopilotPlugin {
+    fn build(&self, app: &mut App) {
+        // taps have to be decided before the input phase reads them
+        app.init_resource::<Autopilot>()
+            .add_system_to_stage(CoreStage::PreUpdate, autopilot_system);
+    }
+}
+
+/// Inserted before the plugin to fly from the start, or changed at any time.
+pub struct Autopilot {
+    pub enabled: bool,
+    pub strategy: AutopilotStrategy,
+    /// start a new run from the start screen, and again a moment after every run is over
+    pub restart: bool,
+}
+
+impl Default for Autopilot {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            strategy: AutopilotStrategy::Lookahead,
+            restart: false,
+        }
+    }
+}
+
+#[derive(Serialize, Deserialize, Clone, Copy, PartialEq, Debug)]
+pub enum AutopilotStrategy {
+    /// Flap whenever falling below the gap of the next pillar. Cheap, but clips the edges of
+    /// the pillars when consecutive gaps are far apart.
+    Heuristic,
+    /// Simulate the flight ahead and only flap when not flapping would lead into a pillar or
+    /// out of the playfield.
+    Lookahead,
+}
+
+impl std::str::FromStr for AutopilotStrategy {
+    type Err = String;
+
+    fn from_str(value: &str) -> Result<Self, Self::Err> {
+        match value {
+            "heuristic" => Ok(AutopilotStrategy::Heuristic),
+            "lookahead" => Ok(AutopilotStrategy::Lookahead),
+            _ => Err(format!(
+                "invalid autopilot strategy {}, expected heuristic or lookahead",
+                value
+            )),
+        }
+    }
+}
+
+/// Seconds the game over screen is shown before the autopilot restarts.
+const RESTART_DELAY: f32 = 1.5;
+
+/// How far below the middle of the gap the heuristic lets the bird sink before flapping.
+const FLAP_BELOW_GAP: f32 = 30.0;
+
+/// Length of a simulated frame.
+const SIMULATION_STEP: f32 = 1.0 / 60.0;
+/// Frames between two points at which the simulation may flap.
+const DECISION_FRAMES: u32 = 6;
+/// Decision points looked ahead, 0.8 seconds in all.
+const LOOKAHEAD_DECISIONS: u32 = 8;
+/// Kept between the simulated bird and anything it may hit, to make up for the frames of the
+/// game not being as long as the simulated ones.
+const SAFETY_MARGIN: f32 = 4.0;
+
+fn autopilot_system(
+    mut engaged: Local<bool>,
+    mut game_over_time: Local<f32>,
+    autopilot: Res<Autopilot>,
+    time: Res<GameClock>,
+    game_state: Res<GameState>,
+    playfield: Res<Playfield>,
+    difficulty: Res<Difficulty>,
+    tuning: Res<Tuning>,
+    score: Res<Score>,
+    mut control: ResMut<PlayerControl>,
+    player_query: Query<(&Transform, &Mover), With<Player>>,
+    pillar_query: Query<(&Transform, &Mover), With<Pillar>>,
+    mut killed_events: EventReader<PlayerKilledEvent>,
+    mut start_new_events: EventWriter<StartNewGameEvent>,
+) {
+    if !autopilot.enabled {
+        // give the controls back once, leaving them alone for anything else scripting them
+        if *engaged {
+            *engaged = false;
+            control.scripted = false;
+            control.tap = false;
+        }
+        return;
+    }
+
+    *engaged = true;
+    control.scripted = true;
+    control.tap = false;
+
+    killed_events.iter().for_each(|event| {
+        info!(
+            "Autopilot run over with a score of {} after {:.1}s, hit {:?}",
+            score.0, event.run_time, event.cause
+        );
+    });
+
+    match game_state.0 {
+        GameStateType::Playing => {
+            let (transform, mover) = player_query.single();
+            let flight = Flight {
+                y: transform.translation.y,
+                velocity: mover.velocity.y,
+                time: 0.0,
+            };
+            let course = Course {
+                pillars: pillar_query
+                    .iter()
+                    .filter(|(_, mover)| mover.active)
+                    .map(|(transform, mover)| (transform.translation, mover.velocity.x))
+                    .collect(),
+                gap: difficulty.pillar_gap(&tuning) - 2.0 * SAFETY_MARGIN,
+                gravity: mover.acceleration.y,
+                floor: -playfield.height / 2.0 + SAFETY_MARGIN,
+                ceiling: playfield.height / 2.0 - SAFETY_MARGIN,
+            };
+
+            control.tap = match autopilot.strategy {
+                AutopilotStrategy::Heuristic => course.heuristic_flap(&flight),
+                AutopilotStrategy::Lookahead => course.lookahead_flap(&flight),
+            };
+        }
+        GameStateType::StartScreen if autopilot.restart => {
+            start_new_events.send(StartNewGameEvent);
+        }
+        GameStateType::GameOver if autopilot.restart => {
+            *game_over_time += time.delta_seconds();
+            if *game_over_time >= RESTART_DELAY {
+                *game_over_time = 0.0;
+                start_new_events.send(StartNewGameEvent);
+            }
+        }
+        _ => {}
+    }
+}
+
+/// Where the bird is and where it is heading, `time` seconds from now.
+#[derive(Clone, Copy)]
+struct Flight {
+    y: f32,
+    velocity: f32,
+    time: f32,
+}
+
+/// What the bird flies through: the active pillars with their horizontal speed, and the
+/// limits of the playfield.
+struct Course {
+    pillars: Vec<(Vec3, f32)>,
+    gap: f32,
+    gravity: f32,
+    floor: f32,
+    ceiling: f32,
+}
+
+impl Course {
+    /// Height of the middle of the gap the bird has to go through next.
+    fn next_gap_y(&self) -> f32 {
+        self.pillars
+            .iter()
+            .map(|(translation, _)| *translation)
+            .filter(|translation| translation.x > -(PILLAR_WIDTH / 2.0))
+            .min_by(|a, b| a.x.total_cmp(&b.x))
+            .map_or(0.0, |translation| translation.y)
+    }
+
+    fn heuristic_flap(&self, flight: &Flight) -> bool {
+        flight.velocity <= 0.0 && flight.y < self.next_gap_y() - FLAP_BELOW_GAP
+    }
+
+    /// Flaps only if there is no way through the time looked ahead without flapping now. When
+    /// there is no way through either way, falls back on the heuristic.
+    fn lookahead_flap(&self, flight: &Flight) -> bool {
+        if self.survives(self.fly(*flight, false, 1), LOOKAHEAD_DECISIONS) {
+            false
+        } else if self.survives(self.fly(*flight, true, 1), LOOKAHEAD_DECISIONS) {
+            true
+        } else {
+            self.heuristic_flap(flight)
+        }
+    }
+
+    /// Whether some sequence of flaps at the coming decision points keeps the bird clear of
+    /// everything. Not flapping is tried first, so the search ends on the calmest way through.
+    fn survives(&self, flight: Option<Flight>, decisions: u32) -> bool {
+        match flight {
+            None => false,
+            Some(_) if decisions == 0 => true,
+            Some(flight) => [false, true]
+                .into_iter()
+                .any(|flap| self.survives(self.fly(flight, flap, DECISION_FRAMES), decisions - 1)),
+        }
+    }
+
+    /// Simulates `frames` frames the way the game does, flapping in the first one if `flap`.
+    /// Returns nothing if the bird hits anything on the way.
+    fn fly(&self, mut flight: Flight, flap: bool, frames: u32) -> Option<Flight> {
+        if flap {
+            flight.velocity = LEAP_Y_VELOCITY;
+        }
+
+        for _ in 0..frames {
+            flight.velocity += self.gravity * SIMULATION_STEP;
+            flight.y += flight.velocity * SIMULATION_STEP;
+            flight.time += SIMULATION_STEP;
+
+            if flight.y < self.floor || flight.y > self.ceiling {
+                return None;
+            }
+
+            let hit = self.pillars.iter().any(|(translation, speed)| {
+                let pillar = *translation + Vec3::X * (*speed * flight.time);
+                pillar_hit(flight.y, pillar, self.gap).is_some()
+            });
+            if hit {
+                return None;
+            }
+        }
+
+        Some(flight)
+    }
+}
@@ -1,6 +1,6 @@
 use bevy::prelude::*;
 
-use crate::loading::LoadingAssets;
+use crate::{game_assets::GameAssets, theme::ThemedSprite};
 
 pub struct BackgroundPlugin;
 
@@ -10,20 +10,15 @@ impl Plugin for BackgroundPlugin {
     }
 }
 
-fn setup(
-    mut commands: Commands,
-    asset_server: Res<AssetServer>,
-    mut loading: ResMut<LoadingAssets>,
-) {
-    let background = asset_server.load("background.png");
-    loading.0.push(background.clone_untyped());
-
-    commands.spawn_bundle(SpriteBundle {
-        texture: background,
-        transform: Transform {
-            translation: Vec3::new(0.0, 0.0, -1.0),
+fn setup(mut commands: Commands, game_assets: Res<GameAssets>) {
+    commands
+        .spawn_bundle(SpriteBundle {
+            texture: game_assets.texture("background"),
+            transform: Transform {
+                translation: Vec3::new(0.0, 0.0, -1.0),
+                ..Default::default()
+            },
             ..Default::default()
-        },
-        ..Default::default()
-    });
+        })
+        .insert(ThemedSprite("background"));
 }
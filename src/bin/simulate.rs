@@ -0,0 +1,370 @@
+//! Plays many headless games with the autopilot and reports how far it gets, to see how a
+//! change to the pillars or the physics affects the difficulty.
+
+use std::{
+    collections::BTreeMap,
+    path::PathBuf,
+    sync::{
+        atomic::{AtomicUsize, Ordering},
+        mpsc, Arc,
+    },
+};
+
+use flappy_bird_bevy::{
+    autopilot::AutopilotStrategy,
+    env::{Action, GameEnv},
+    game_core::Tuning,
+    pillars::Difficulty,
+};
+use serde::Serialize;
+
+const USAGE: &str = "\
+Usage: simulate [OPTIONS]
+
+Options:
+  --games <N>              number of games to play, 1000 by default
+  --policy <STRATEGY>      heuristic or lookahead, the latter by default
+  --difficulty <LEVEL>     easy, normal or hard, normal by default
+  --gap <HEIGHT>           height of the opening between the pillars, instead of the one of the
+                           difficulty
+  --speed <SPEED>          speed of the pillars, instead of the one of the difficulty
+  --gravity <ACCELERATION> downward acceleration of the bird, instead of the one of the game
+  --seed <N>               seed of the first game, the others counting up from it, 0 by default
+  --max-seconds <S>        end runs that last this long, 300 by default
+  --threads <N>            games played at once, one per core by default
+  --csv <FILE>             write the result of every game to FILE
+  --json <FILE>            write the report to FILE
+  --help                   print this help
+";
+
+/// Scores reported as percentiles.
+const PERCENTILES: [usize; 5] = [10, 25, 50, 75, 90];
+
+/// The death cause reported for runs that reach `--max-seconds`.
+const TIMED_OUT: &str = "TimedOut";
+
+struct Options {
+    games: usize,
+    policy: AutopilotStrategy,
+    difficulty: Difficulty,
+    tuning: Tuning,
+    seed: u64,
+    max_seconds: f32,
+    threads: usize,
+    csv: Option<PathBuf>,
+    json: Option<PathBuf>,
+}
+
+impl Options {
+    fn parse(mut args: impl Iterator<Item = String>) -> Result<Option<Self>, String> {
+        let mut options = Options {
+            games: 1000,
+            policy: AutopilotStrategy::Lookahead,
+            difficulty: Difficulty::Normal,
+            tuning: Tuning::default(),
+            seed: 0,
+            max_seconds: 300.0,
+            threads: std::thread::available_parallelism().map_or(1, |threads| threads.get()),
+            csv: None,
+            json: None,
+        };
+
+        while let Some(arg) = args.next() {
+            let value = args.next().ok_or_else(|| format!("{} needs a value", arg));
+
+            match arg.as_str() {
+                "--help" | "-h" => return Ok(None),
+                "--games" => options.games = parse_number(&arg, value?)?,
+                "--policy" => options.policy = value?.parse()?,
+                "--difficulty" => options.difficulty = value?.parse()?,
+                "--gap" => options.tuning.pillar_gap = Some(parse_number(&arg, value?)?),
+                "--speed" => options.tuning.pillar_speed = Some(parse_number(&arg, value?)?),
+                "--gravity" => options.tuning.player_gravity = Some(parse_number(&arg, value?)?),
+                "--seed" => options.seed = parse_number(&arg, value?)?,
+                "--max-seconds" => options.max_seconds = parse_number(&arg, value?)?,
+                "--threads" => options.threads = parse_number::<usize>(&arg, value?)?.max(1),
+                "--csv" => options.csv = Some(value?.into()),
+                "--json" => options.json = Some(value?.into()),
+                _ => return Err(format!("unknown option {}", arg)),
+            }
+        }
+
+        Ok(Some(options))
+    }
+}
+
+fn parse_number<T: std::str::FromStr>(arg: &str, value: String) -> Result<T, String> {
+    value
+        .parse()
+        .map_err(|_| format!("invalid value {} for {}", value, arg))
+}
+
+struct GameResult {
+    game: usize,
+    seed: u64,
+    score: u32,
+    run_time: f32,
+    cause: String,
+}
+
+#[derive(Serialize)]
+struct Report {
+    games: usize,
+    policy: AutopilotStrategy,
+    difficulty: Difficulty,
+    /// the values played with in place of the ones of the game, if any
+    tuning: Tuning,
+    seed: u64,
+    max_seconds: f32,
+    mean_score: f32,
+    min_score: u32,
+    max_score: u32,
+    /// score under which the given percentage of the games ended
+    score_percentiles: BTreeMap<usize, u32>,
+    /// number of games that ended with each score
+    score_histogram: BTreeMap<u32, usize>,
+    death_causes: BTreeMap<String, usize>,
+    mean_survival_seconds: f32,
+}
+
+impl Report {
+    fn new(options: &Options, results: &[GameResult]) -> Self {
+        let mut scores: Vec<u32> = results.iter().map(|result| result.score).collect();
+        scores.sort_unstable();
+        let games = results.len().max(1) as f32;
+
+        let mut score_histogram = BTreeMap::new();
+        let mut death_causes = BTreeMap::new();
+        results.iter().for_each(|result| {
+            *score_histogram.entry(result.score).or_insert(0) += 1;
+            *death_causes.entry(result.cause.clone()).or_insert(0) += 1;
+        });
+
+        Self {
+            games: results.len(),
+            policy: options.policy,
+            difficulty: options.difficulty,
+            tuning: options.tuning,
+            seed: options.seed,
+            max_seconds: options.max_seconds,
+            mean_score: scores.iter().sum::<u32>() as f32 / games,
+            min_score: scores.first().copied().unwrap_or(0),
+            max_score: scores.last().copied().unwrap_or(0),
+            score_percentiles: PERCENTILES
+                .into_iter()
+                .map(|percentile| {
+                    let index = (scores.len() * percentile / 100).min(scores.len().max(1) - 1);
+                    (percentile, scores.get(index).copied().unwrap_or(0))
+                })
+                .collect(),
+            score_histogram,
+            death_causes,
+            mean_survival_seconds: results.iter().map(|result| result.run_time).sum::<f32>()
+                / games,
+        }
+    }
+
+    fn print_summary(&self) {
+        println!(
+            "{} games, {:?} policy on {:?}",
+            self.games, self.policy, self.difficulty
+        );
+        if self.tuning != Tuning::default() {
+            println!("tuned: {:?}", self.tuning);
+        }
+        println!(
+            "score: mean {:.2}, min {}, max {}",
+            self.mean_score, self.min_score, self.max_score
+        );
+        self.score_percentiles
+            .iter()
+            .for_each(|(percentile, score)| println!("  p{}: {}", percentile, score));
+        println!("mean survival: {:.1}s", self.mean_survival_seconds);
+        println!("death causes:");
+        self.death_causes.iter().for_each(|(cause, count)| {
+            println!(
+                "  {}: {} ({:.1}%)",
+                cause,
+                count,
+                100.0 * *count as f32 / self.games.max(1) as f32
+            )
+        });
+    }
+}
+
+fn play(env: &mut GameEnv, game: usize, seed: u64, max_seconds: f32) -> GameResult {
+    env.reset(Some(seed));
+
+    // the autopilot flies, so the action does not matter
+    while !env.step(Action::Idle).2 && env.run_time() < max_seconds {}
+
+    GameResult {
+        game,
+        seed,
+        score: env.score(),
+        run_time: env.run_time(),
+        cause: env
+            .last_death()
+            .map_or(TIMED_OUT.to_string(), |death| format!("{:?}", death.cause)),
+    }
+}
+
+fn simulate(options: &Options) -> Vec<GameResult> {
+    let next_game = Arc::new(AtomicUsize::new(0));
+    let (sender, receiver) = mpsc::channel();
+
+    (0..options.threads.min(options.games)).for_each(|_| {
+        let next_game = next_game.clone();
+        let sender = sender.clone();
+        let (games, policy, difficulty, tuning, seed, max_seconds) = (
+            options.games,
+            options.policy,
+            options.difficulty,
+            options.tuning,
+            options.seed,
+            options.max_seconds,
+        );
+
+        std::thread::spawn(move || {
+            let mut env = GameEnv::tuned(difficulty, tuning).with_autopilot(policy);
+
+            loop {
+                let game = next_game.fetch_add(1, Ordering::Relaxed);
+                if game >= games {
+                    break;
+                }
+
+                let result = play(&mut env, game, seed.wrapping_add(game as u64), max_seconds);
+                if sender.send(result).is_err() {
+                    break;
+                }
+            }
+        });
+    });
+    drop(sender);
+
+    let mut results = Vec::with_capacity(options.games);
+    receiver.iter().for_each(|result| {
+        results.push(result);
+        if results.len() % (options.games / 10).max(1) == 0 {
+            eprintln!("{}/{} games", results.len(), options.games);
+        }
+    });
+
+    results.sort_by_key(|result| result.game);
+    results
+}
+
+fn write_csv(path: &std::path::Path, results: &[GameResult]) -> Result<(), String> {
+    let mut csv = String::from("game,seed,score,run_time,death_cause\n");
+    results.iter().for_each(|result| {
+        csv.push_str(&format!(
+            "{},{},{},{:.3},{}\n",
+            result.game, result.seed, result.score, result.run_time, result.cause
+        ));
+    });
+    std::fs::write(path, csv).map_err(|err| err.to_string())
+}
+
+fn write_json(path: &std::path::Path, report: &Report) -> Result<(), String> {
+    let json = serde_json::to_string_pretty(report).map_err(|err| err.to_string())?;
+    std::fs::write(path, json).map_err(|err| err.to_string())
+}
+
+fn main() {
+    let options = match Options::parse(std::env::args().skip(1)) {
+        Ok(Some(options)) => options,
+        Ok(None) => {
+            print!("{}", USAGE);
+            return;
+        }
+        Err(err) => {
+            eprintln!("error: {}\n\n{}", err, USAGE);
+            std::process::exit(2);
+        }
+    };
+
+    let results = simulate(&options);
+    let report = Report::new(&options, &results);
+    report.print_summary();
+
+    let written = [
+        options
+            .csv
+            .as_ref()
+            .map(|path| (path, write_csv(path, &results))),
+        options
+            .json
+            .as_ref()
+            .map(|path| (path, write_json(path, &report))),
+    ];
+    let mut failed = false;
+    written.into_iter().flatten().for_each(|(path, result)| {
+        if let Err(err) = result {
+            eprintln!("error: cannot write {}: {}", path.display(), err);
+            failed = true;
+        }
+    });
+
+    if failed {
+        std::process::exit(1);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn result(game: usize, score: u32, cause: &str) -> GameResult {
+        GameResult {
+            game,
+            seed: game as u64,
+            score,
+            run_time: score as f32,
+            cause: cause.to_string(),
+        }
+    }
+
+    fn options() -> Options {
+        Options::parse(std::iter::empty()).unwrap().unwrap()
+    }
+
+    #[test]
+    fn report_percentiles_histogram_and_causes() {
+        let results: Vec<GameResult> = [3, 0, 7, 1, 9, 3, 5, 2, 8, 3]
+            .into_iter()
+            .enumerate()
+            .map(|(game, score)| {
+                let cause = if score == 9 { TIMED_OUT } else { "Floor" };
+                result(game, score, cause)
+            })
+            .collect();
+
+        let report = Report::new(&options(), &results);
+
+        assert_eq!(report.games, 10);
+        assert_eq!((report.min_score, report.max_score), (0, 9));
+        assert!((report.mean_score - 4.1).abs() < 1e-5);
+        assert!((report.mean_survival_seconds - 4.1).abs() < 1e-5);
+        // sorted: 0 1 2 3 3 3 5 7 8 9
+        assert_eq!(
+            report.score_percentiles.into_iter().collect::<Vec<_>>(),
+            vec![(10, 1), (25, 2), (50, 3), (75, 7), (90, 9)]
+        );
+        assert_eq!(report.score_histogram.get(&3), Some(&3));
+        assert_eq!(report.score_histogram.values().sum::<usize>(), 10);
+        assert_eq!(report.death_causes.get("Floor"), Some(&9));
+        assert_eq!(report.death_causes.get(TIMED_OUT), Some(&1));
+    }
+
+    #[test]
+    fn report_without_games() {
+        let report = Report::new(&options(), &[]);
+
+        assert_eq!(report.games, 0);
+        assert_eq!((report.min_score, report.max_score), (0, 0));
+        assert_eq!(report.mean_score, 0.0);
+        assert!(report.score_percentiles.values().all(|score| *score == 0));
+        assert!(report.score_histogram.is_empty());
+    }
+}
@@ -0,0 +1,230 @@
+use std::{path::PathBuf, time::Duration};
+
+use bevy::prelude::*;
+use flappy_bird_bevy::{
+    audio::AudioSettings,
+    autopilot::{Autopilot, AutopilotStrategy},
+    game_core::{GameClock, GameRng},
+    ghost::GhostSettings,
+    pillars::Difficulty,
+    replay::{Replay, ReplayMode},
+};
+use serde::Deserialize;
+
+pub const USAGE: &str = "\
+Usage: flappy_bird_bevy [OPTIONS]
+
+Options:
+  --seed <N>                 seed the pillars of every run from N
+  --difficulty <LEVEL>       easy, normal or hard, the one a run played back or raced was
+                             recorded on by default
+  --window-size <W>x<H>      size of the window, e.g. 800x600
+  --headless                 run without a window or sound as fast as possible, needs
+                             --replay or --autopilot
+  --replay <FILE>            play back the run recorded in FILE
+  --record <FILE>            record every run to FILE, replacing the one before
+  --autopilot <STRATEGY>     let the game play itself with the heuristic or lookahead
+                             strategy, restarting after every run
+  --ghost                    race a ghost flying your best run, on the same pillars
+  --ghost-replay <FILE>      race a ghost flying the run recorded in FILE instead
+  --config <FILE>            read any of the above settings from a RON file
+  --mute                     start with the sound off
+  --dump-schedule            print the systems of every stage instead of running the game
+  --help                     print this help
+";
+
+/// Settings that can be given on the command line as well as in the config file. The command
+/// line wins over the file.
+#[derive(Deserialize, Default)]
+#[serde(default)]
+struct LaunchConfig {
+    seed: Option<u64>,
+    difficulty: Option<Difficulty>,
+    window_size: Option<(f32, f32)>,
+    muted: bool,
+    replay: Option<PathBuf>,
+    record: Option<PathBuf>,
+    autopilot: Option<AutopilotStrategy>,
+    ghost: bool,
+    ghost_replay: Option<PathBuf>,
+}
+
+impl LaunchConfig {
+    fn load(path: &std::path::Path) -> Result<Self, String> {
+        let contents = std::fs::read_to_string(path)
+            .map_err(|err| format!("cannot read config {}: {}", path.display(), err))?;
+        ron::from_str(&contents)
+            .map_err(|err| format!("cannot parse config {}: {}", path.display(), err))
+    }
+
+    fn or(self, fallback: LaunchConfig) -> Self {
+        Self {
+            seed: self.seed.or(fallback.seed),
+            difficulty: self.difficulty.or(fallback.difficulty),
+            window_size: self.window_size.or(fallback.window_size),
+            muted: self.muted || fallback.muted,
+            replay: self.replay.or(fallback.replay),
+            record: self.record.or(fallback.record),
+            autopilot: self.autopilot.or(fallback.autopilot),
+            ghost: self.ghost || fallback.ghost,
+            ghost_replay: self.ghost_replay.or(fallback.ghost_replay),
+        }
+    }
+}
+
+pub struct Options {
+    pub help: bool,
+    pub headless: bool,
+    pub dump_schedule: bool,
+    config: Option<PathBuf>,
+    launch: LaunchConfig,
+}
+
+impl Options {
+    pub fn parse(mut args: impl Iterator<Item = String>) -> Result<Self, String> {
+        let mut options = Options {
+            help: false,
+            headless: false,
+            dump_schedule: false,
+            config: None,
+            launch: LaunchConfig::default(),
+        };
+
+        while let Some(arg) = args.next() {
+            let mut value = || args.next().ok_or_else(|| format!("{} needs a value", arg));
+
+            match arg.as_str() {
+                "--help" | "-h" => options.help = true,
+                "--headless" => options.headless = true,
+                "--dump-schedule" => options.dump_schedule = true,
+                "--mute" => options.launch.muted = true,
+                "--ghost" => options.launch.ghost = true,
+                "--seed" => {
+                    let seed = value()?;
+                    options.launch.seed =
+                        Some(seed.parse().map_err(|_| format!("invalid seed {}", seed))?);
+                }
+                "--difficulty" => options.launch.difficulty = Some(value()?.parse()?),
+                "--window-size" => options.launch.window_size = Some(parse_window_size(&value()?)?),
+                "--replay" => options.launch.replay = Some(value()?.into()),
+                "--record" => options.launch.record = Some(value()?.into()),
+                "--autopilot" => options.launch.autopilot = Some(value()?.parse()?),
+                "--ghost-replay" => options.launch.ghost_replay = Some(value()?.into()),
+                "--config" => options.config = Some(value()?.into()),
+                _ => return Err(format!("unknown option {}", arg)),
+            }
+        }
+
+        Ok(options)
+    }
+
+    /// Inserts the resources the plugins read at startup, which has to happen before they are
+    /// added.
+    pub fn configure(self, app: &mut App) -> Result<(), String> {
+        let launch = match &self.config {
+            Some(path) => self.launch.or(LaunchConfig::load(path)?),
+            None => self.launch,
+        };
+
+        let replay = launch
+            .replay
+            .as_ref()
+            .map(|path| {
+                Replay::load(path)
+                    .map_err(|err| format!("cannot load replay {}: {}", path.display(), err))
+            })
+            .transpose()?;
+        let ghost_replay = launch
+            .ghost_replay
+            .as_ref()
+            .map(|path| {
+                Replay::load(path)
+                    .map_err(|err| format!("cannot load ghost replay {}: {}", path.display(), err))
+            })
+            .transpose()?;
+
+        if replay.is_some() && launch.autopilot.is_some() {
+            return Err("a run cannot be played back with the autopilot on".to_string());
+        }
+        if self.headless && replay.is_none() && launch.autopilot.is_none() {
+            return Err("--headless needs --replay or --autopilot to play the game".to_string());
+        }
+
+        if let Some((width, height)) = launch.window_size {
+            app.insert_resource(WindowDescriptor {
+                width,
+                height,
+                ..Default::default()
+            });
+        }
+        if let Some(seed) = launch.seed {
+            app.insert_resource(GameRng::seeded(seed));
+        }
+        if let Some(strategy) = launch.autopilot {
+            app.insert_resource(Autopilot {
+                enabled: true,
+                strategy,
+                restart: true,
+            });
+
+            if self.headless {
+                // frames run back to back, the game has to keep its own pace
+                app.insert_resource(GameClock::fixed(Duration::from_secs_f32(1.0 / 60.0)));
+            }
+        }
+
+        // a run is played back or raced on the difficulty it was recorded on
+        let recorded = replay
+            .as_ref()
+            .or(ghost_replay.as_ref())
+            .map(|replay| replay.difficulty);
+        if let (Some(recorded), Some(difficulty)) = (recorded, launch.difficulty) {
+            if recorded != difficulty {
+                return Err(format!(
+                    "the run was recorded on {:?} and cannot be played on {:?}",
+                    recorded, difficulty
+                ));
+            }
+        }
+        let difficulty = recorded.or(launch.difficulty).unwrap_or_default();
+
+        let replay_mode = match (replay, launch.record) {
+            (Some(_), Some(_)) => {
+                return Err("a run cannot be played back and recorded at once".to_string())
+            }
+            (Some(replay), None) => ReplayMode::Play {
+                replay,
+                exit_when_done: self.headless,
+            },
+            (None, Some(path)) => ReplayMode::Record(path),
+            (None, None) => ReplayMode::Off,
+        };
+
+        app.insert_resource(difficulty)
+            // a headless run may have no audio device to play on
+            .insert_resource(AudioSettings {
+                muted: launch.muted || self.headless,
+            })
+            .insert_resource(replay_mode)
+            .insert_resource(GhostSettings {
+                enabled: launch.ghost || ghost_replay.is_some(),
+                replay: ghost_replay,
+            });
+
+        Ok(())
+    }
+}
+
+fn parse_window_size(value: &str) -> Result<(f32, f32), String> {
+    let size = value.split_once('x').and_then(|(width, height)| {
+        Some((width.parse::<f32>().ok()?, height.parse::<f32>().ok()?))
+    });
+
+    match size {
+        Some((width, height)) if width > 0.0 && height > 0.0 => Ok((width, height)),
+        _ => Err(format!(
+            "invalid window size {}, expected e.g. 800x600",
+            value
+        )),
+    }
+}
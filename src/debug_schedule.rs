@@ -0,0 +1,117 @@
+use std::fmt::{Debug, Write};
+
+use bevy::{
+    ecs::schedule::{GraphNode, SystemContainer},
+    prelude::*,
+};
+
+/// Lists the systems of every stage in an order satisfying their `before`/`after` constraints,
+/// along with their labels. Systems without constraints between them may run in any order or
+/// in parallel, so this is one valid order rather than the one a given frame will use.
+pub fn dump_schedule(schedule: &Schedule) -> String {
+    let mut dump = String::new();
+
+    schedule.iter_stages().for_each(|(stage_label, stage)| {
+        let _ = writeln!(dump, "{:?}", stage_label);
+
+        if let Some(stage) = stage.downcast_ref::<SystemStage>() {
+            [
+                (
+                    "exclusive at start",
+                    dump_systems(stage.exclusive_at_start_systems()),
+                ),
+                ("parallel", dump_systems(stage.parallel_systems())),
+                (
+                    "exclusive before commands",
+                    dump_systems(stage.exclusive_before_commands_systems()),
+                ),
+                (
+                    "exclusive at end",
+                    dump_systems(stage.exclusive_at_end_systems()),
+                ),
+            ]
+            .into_iter()
+            .filter(|(_, systems)| !systems.is_empty())
+            .for_each(|(kind, systems)| {
+                let _ = writeln!(dump, "  {}", kind);
+                systems.iter().for_each(|line| {
+                    let _ = writeln!(dump, "    {}", line);
+                });
+            });
+        } else if let Some(schedule) = stage.downcast_ref::<Schedule>() {
+            dump_schedule(schedule).lines().for_each(|line| {
+                let _ = writeln!(dump, "  {}", line);
+            });
+        }
+    });
+
+    dump
+}
+
+fn dump_systems(systems: &[impl SystemContainer]) -> Vec<String> {
+    sort_systems(systems)
+        .into_iter()
+        .map(|(index, in_cycle)| {
+            let system = &systems[index];
+            let mut line = system.name().to_string();
+
+            if !system.labels().is_empty() {
+                let _ = write!(line, " [{}]", label_names(system.labels()).join(", "));
+            }
+            if in_cycle {
+                line.push_str(" (ordering cycle)");
+            }
+
+            line
+        })
+        .collect()
+}
+
+/// Topologically sorts the systems, keeping the order they were added in where the constraints
+/// allow it. Systems caught in a cycle come last and are flagged.
+fn sort_systems(systems: &[impl SystemContainer]) -> Vec<(usize, bool)> {
+    let labels: Vec<Vec<String>> = systems
+        .iter()
+        .map(|system| label_names(system.labels()))
+        .collect();
+    let has_label = |index: usize, label: &String| labels[index].contains(label);
+
+    // dependencies[i] are the systems that have to run before system i
+    let mut dependencies: Vec<Vec<usize>> = vec![vec![]; systems.len()];
+    systems.iter().enumerate().for_each(|(index, system)| {
+        label_names(system.after()).iter().for_each(|label| {
+            (0..systems.len())
+                .filter(|other| *other != index && has_label(*other, label))
+                .for_each(|other| dependencies[index].push(other));
+        });
+        label_names(system.before()).iter().for_each(|label| {
+            (0..systems.len())
+                .filter(|other| *other != index && has_label(*other, label))
+                .for_each(|other| dependencies[other].push(index));
+        });
+    });
+
+    let mut sorted = Vec::with_capacity(systems.len());
+    let mut placed = vec![false; systems.len()];
+
+    while let Some(next) = (0..systems.len()).find(|index| {
+        !placed[*index]
+            && dependencies[*index]
+                .iter()
+                .all(|dependency| placed[*dependency])
+    }) {
+        placed[next] = true;
+        sorted.push((next, false));
+    }
+
+    sorted.extend(
+        (0..systems.len())
+            .filter(|index| !placed[*index])
+            .map(|index| (index, true)),
+    );
+    sorted
+}
+
+fn label_names(labels: &[impl Debug]) -> Vec<String> {
+    labels.iter().map(|label| format!("{:?}", label)).collect()
+}
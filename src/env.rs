@@ -0,0 +1,291 @@
+//! A gym-style environment for training agents: the game without a window, stepped one fixed
+//! frame per action.
+
+use std::time::Duration;
+
+use bevy::{app::ManualEventReader, prelude::*};
+
+use crate::{
+    autopilot::{Autopilot, AutopilotStrategy},
+    game_core::{GameClock, GameRng, Playfield, Tuning},
+    game_state::{GameState, GameStateType, StartNewGameEvent},
+    headless::headless_app,
+    mover::Mover,
+    pillars::{Difficulty, Pillar, PILLAR_WIDTH},
+    player::{Player, PlayerControl, PlayerCrossedPillarEvent, PlayerKilledEvent, RunTime},
+    score::Score,
+};
+
+/// Length of the frame every step runs, in seconds.
+pub const STEP: f32 = 1.0 / 60.0;
+
+/// Frames a reset may take to get from any state to the start of a run, enough for the
+/// countdown to run out when the autopilot does not tap through it.
+const RESET_FRAMES: u32 = 240;
+
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub enum Action {
+    Idle,
+    Flap,
+}
+
+/// What an agent sees of the game, in playfield units with the bird at x 0 and the middle of
+/// the playfield at y 0.
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub struct Observation {
+    pub bird_y: f32,
+    /// vertical speed of the bird, positive upwards
+    pub bird_velocity: f32,
+    /// the next two pillars not yet passed, nearest first
+    pub pillars: [PillarObservation; 2],
+}
+
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub struct PillarObservation {
+    /// horizontal distance from the bird to the centre of the pillar
+    pub x: f32,
+    /// height of the middle of the gap
+    pub gap_y: f32,
+}
+
+impl Observation {
+    /// The observation as a flat vector: bird y and velocity, then x and gap y of each pillar.
+    pub fn to_array(&self) -> [f32; 6] {
+        [
+            self.bird_y,
+            self.bird_velocity,
+            self.pillars[0].x,
+            self.pillars[0].gap_y,
+            self.pillars[1].x,
+            self.pillars[1].gap_y,
+        ]
+    }
+}
+
+/// What each step is worth.
+#[derive(Clone, Copy, Debug)]
+pub struct Rewards {
+    /// for every pillar crossed
+    pub crossed: f32,
+    /// for the step the bird dies in
+    pub killed: f32,
+    /// for every other step survived
+    pub alive: f32,
+}
+
+impl Default for Rewards {
+    fn default() -> Self {
+        Self {
+            crossed: 1.0,
+            killed: -1.0,
+            alive: 0.01,
+        }
+    }
+}
+
+/// The game without a window, sound or save files, on a clock that advances `STEP` seconds
+/// per step. Its taps come from the actions instead of the input devices.
+pub struct GameEnv {
+    app: App,
+    rewards: Rewards,
+    crossed_reader: ManualEventReader<PlayerCrossedPillarEvent>,
+    killed_reader: ManualEventReader<PlayerKilledEvent>,
+    last_death: Option<PlayerKilledEvent>,
+}
+
+impl GameEnv {
+    /// Builds the game and waits for its assets. Panics if they do not load.
+    pub fn new(difficulty: Difficulty) -> Self {
+        Self::tuned(difficulty, Tuning::default())
+    }
+
+    /// Builds the game with the values of the `Tuning` in place of its own, as `new` does.
+    pub fn tuned(difficulty: Difficulty, tuning: Tuning) -> Self {
+        let app = headless_app(|app| {
+            app.insert_resource(difficulty)
+                .insert_resource(tuning)
+                .insert_resource(PlayerControl {
+                    scripted: true,
+                    tap: false,
+                });
+        });
+
+        Self {
+            app,
+            rewards: Rewards::default(),
+            crossed_reader: Default::default(),
+            killed_reader: Default::default(),
+            last_death: None,
+        }
+    }
+
+    pub fn with_rewards(mut self, rewards: Rewards) -> Self {
+        self.rewards = rewards;
+        self
+    }
+
+    /// Lets the autopilot fly instead of the agent, the actions passed to `step` being ignored.
+    pub fn with_autopilot(mut self, strategy: AutopilotStrategy) -> Self {
+        self.app.world.insert_resource(Autopilot {
+            enabled: true,
+            strategy,
+            restart: false,
+        });
+        self
+    }
+
+    /// Starts a new run, its pillars drawn from `seed` if given, and returns what it starts
+    /// with. The countdown is skipped with a tap, which is the first flap of the run, unless
+    /// the autopilot flies.
+    pub fn reset(&mut self, seed: Option<u64>) -> Observation {
+        let world = &mut self.app.world;
+        if let Some(seed) = seed {
+            world
+                .get_resource_mut::<GameRng>()
+                .unwrap()
+                .set_next_run_seed(seed);
+        }
+        world
+            .get_resource_mut::<Events<StartNewGameEvent>>()
+            .unwrap()
+            .send(StartNewGameEvent);
+
+        // a tap in the frame the game restarts in would flap the run being left
+        self.tick(false);
+        for _ in 0..RESET_FRAMES {
+            if self.state() == GameStateType::Playing {
+                break;
+            }
+            self.tick(true);
+        }
+        assert_eq!(
+            self.state(),
+            GameStateType::Playing,
+            "the run did not start"
+        );
+
+        // whatever happened before the run does not count towards its first step
+        self.count_events();
+        self.last_death = None;
+        self.observe()
+    }
+
+    /// Plays one frame with the action, returning what it leads to, what it was worth and
+    /// whether the run is over. Once it is, steps do nothing until the next `reset`.
+    pub fn step(&mut self, action: Action) -> (Observation, f32, bool) {
+        if self.state() != GameStateType::Playing {
+            return (self.observe(), 0.0, true);
+        }
+
+        self.tick(action == Action::Flap);
+
+        let (crossed, killed) = self.count_events();
+        let done = killed > 0 || self.state() != GameStateType::Playing;
+
+        let mut reward = crossed as f32 * self.rewards.crossed;
+        reward += if done {
+            self.rewards.killed
+        } else {
+            self.rewards.alive
+        };
+
+        (self.observe(), reward, done)
+    }
+
+    /// The current state of the run, as `step` and `reset` return it.
+    pub fn observe(&mut self) -> Observation {
+        let world = &mut self.app.world;
+
+        let (bird_y, bird_velocity) = world
+            .query_filtered::<(&Transform, &Mover), With<Player>>()
+            .iter(world)
+            .next()
+            .map(|(transform, mover)| (transform.translation.y, mover.velocity.y))
+            .unwrap();
+
+        let mut ahead: Vec<Vec3> = world
+            .query_filtered::<(&Transform, &Mover), With<Pillar>>()
+            .iter(world)
+            .filter(|(transform, mover)| {
+                mover.active && transform.translation.x > -(PILLAR_WIDTH / 2.0)
+            })
+            .map(|(transform, _)| transform.translation)
+            .collect();
+        ahead.sort_by(|a, b| a.x.total_cmp(&b.x));
+
+        // pillars yet to come are seen as waiting just past the right edge, level with the
+        // middle of the playfield
+        let not_spawned = PillarObservation {
+            x: world.get_resource::<Playfield>().unwrap().width / 2.0 + PILLAR_WIDTH / 2.0,
+            gap_y: 0.0,
+        };
+        let mut pillars = [not_spawned; 2];
+        ahead
+            .iter()
+            .zip(pillars.iter_mut())
+            .for_each(|(translation, pillar)| {
+                *pillar = PillarObservation {
+                    x: translation.x,
+                    gap_y: translation.y,
+                }
+            });
+
+        Observation {
+            bird_y,
+            bird_velocity,
+            pillars,
+        }
+    }
+
+    pub fn score(&self) -> u32 {
+        self.app.world.get_resource::<Score>().unwrap().0
+    }
+
+    /// Seconds the current run has been played for.
+    pub fn run_time(&self) -> f32 {
+        self.app.world.get_resource::<RunTime>().unwrap().0
+    }
+
+    /// How the current run ended, once it has.
+    pub fn last_death(&self) -> Option<&PlayerKilledEvent> {
+        self.last_death.as_ref()
+    }
+
+    /// The game itself, to look at anything the observations leave out.
+    pub fn world(&self) -> &World {
+        &self.app.world
+    }
+
+    fn state(&self) -> GameStateType {
+        self.app.world.get_resource::<GameState>().unwrap().0
+    }
+
+    fn tick(&mut self, tap: bool) {
+        let world = &mut self.app.world;
+        world.get_resource_mut::<PlayerControl>().unwrap().tap = tap;
+        world
+            .get_resource_mut::<GameClock>()
+            .unwrap()
+            .advance(Duration::from_secs_f32(STEP));
+
+        self.app.update();
+    }
+
+    /// Pillars crossed and deaths since the last call.
+    fn count_events(&mut self) -> (usize, usize) {
+        let world = &self.app.world;
+        let crossed = self
+            .crossed_reader
+            .iter(world.get_resource().unwrap())
+            .count();
+        let killed: Vec<&PlayerKilledEvent> = self
+            .killed_reader
+            .iter(world.get_resource().unwrap())
+            .collect();
+
+        if let Some(event) = killed.last() {
+            self.last_death = Some((*event).clone());
+        }
+        (crossed, killed.len())
+    }
+}
@@ -0,0 +1,275 @@
+use std::collections::HashMap;
+
+use bevy::{
+    asset::{Asset, AssetServerSettings, FileAssetIo},
+    prelude::*,
+};
+use bevy_kira_audio::AudioSource;
+use serde::{de::DeserializeOwned, Deserialize};
+
+use crate::{
+    game_core::GameSystem,
+    loading::{LoadingAssets, PhaseFailedEvent, PhaseLoadedEvent, BOOT_PHASE},
+};
+
+pub struct GameAssetsPlugin;
+
+impl Plugin for GameAssetsPlugin {
+    fn build(&self, app: &mut App) {
+        // plugins look up their handles during `StartupStage::Startup`,
+        // so the manifest has to be loaded before that
+        app.insert_resource(GameAssets::default())
+            .add_event::<LoadBundleEvent>()
+            .add_startup_system_to_stage(
+                StartupStage::PreStartup,
+                load_game_assets.label(GameAssetsSystem::Manifest),
+            )
+            .add_system_set(
+                GameSystem::State
+                    .set()
+                    .with_system(load_bundle_system)
+                    .with_system(staged_bundle_system),
+            );
+    }
+}
+
+#[derive(SystemLabel, Debug, Clone, PartialEq, Eq, Hash)]
+pub enum GameAssetsSystem {
+    /// Startup system reading the manifest and starting the boot phase
+    Manifest,
+}
+
+const MANIFEST_PATH: &str = "manifest.ron";
+
+/// Requests that a bundle from the manifest is loaded as its own loading phase.
+/// A `PhaseLoadedEvent` with the same name is sent once it is ready, or a `PhaseFailedEvent`
+/// if it cannot be loaded. Its assets are only looked up once it is ready, until then the ones
+/// they replace still are.
+pub struct LoadBundleEvent(pub String);
+
+#[derive(Deserialize, Default)]
+struct AssetManifest {
+    bundles: HashMap<String, AssetBundle>,
+    /// folders under `themes/`, each holding a `theme.ron`
+    #[serde(default)]
+    themes: Vec<String>,
+    /// string tables under `locales/`, the first one being the default language
+    #[serde(default)]
+    languages: Vec<String>,
+    /// fonts tried in order when a text has characters the theme font is missing
+    #[serde(default)]
+    font_fallbacks: Vec<String>,
+}
+
+#[derive(Deserialize, Default, Clone)]
+pub struct AssetBundle {
+    #[serde(default)]
+    pub textures: HashMap<String, String>,
+    #[serde(default)]
+    pub fonts: HashMap<String, String>,
+    #[serde(default)]
+    pub sounds: HashMap<String, String>,
+}
+
+/// Handles to every asset of the loaded manifest bundles, looked up by logical name.
+#[derive(Default)]
+pub struct GameAssets {
+    manifest: AssetManifest,
+    handles: BundleHandles,
+    /// bundles requested by a `LoadBundleEvent` that are still loading, by bundle name
+    staged: HashMap<String, BundleHandles>,
+}
+
+#[derive(Default)]
+struct BundleHandles {
+    textures: HashMap<String, Handle<Image>>,
+    fonts: HashMap<String, Handle<Font>>,
+    sounds: HashMap<String, Handle<AudioSource>>,
+}
+
+impl BundleHandles {
+    fn extend(&mut self, other: BundleHandles) {
+        self.textures.extend(other.textures);
+        self.fonts.extend(other.fonts);
+        self.sounds.extend(other.sounds);
+    }
+}
+
+impl GameAssets {
+    pub fn texture(&self, name: &str) -> Handle<Image> {
+        lookup(&self.handles.textures, "texture", name)
+    }
+
+    pub fn font(&self, name: &str) -> Handle<Font> {
+        lookup(&self.handles.fonts, "font", name)
+    }
+
+    pub fn sound(&self, name: &str) -> Handle<AudioSource> {
+        lookup(&self.handles.sounds, "sound", name)
+    }
+
+    pub fn themes(&self) -> &[String] {
+        &self.manifest.themes
+    }
+
+    pub fn languages(&self) -> &[String] {
+        &self.manifest.languages
+    }
+
+    pub fn font_fallbacks(&self) -> &[String] {
+        &self.manifest.font_fallbacks
+    }
+
+    /// Registers a bundle that is not listed in the manifest itself.
+    pub fn add_bundle(&mut self, name: &str, bundle: AssetBundle) {
+        self.manifest.bundles.insert(name.to_string(), bundle);
+    }
+
+    /// Loads a bundle as part of a loading phase that is already in progress.
+    /// Its assets replace any previously loaded assets with the same names right away.
+    pub fn load_bundle(
+        &mut self,
+        name: &str,
+        phase: &str,
+        asset_server: &AssetServer,
+        loading: &mut LoadingAssets,
+    ) -> Result<(), String> {
+        let handles = self.load_handles(name, phase, asset_server, loading)?;
+        self.handles.extend(handles);
+        Ok(())
+    }
+
+    fn load_handles(
+        &self,
+        name: &str,
+        phase: &str,
+        asset_server: &AssetServer,
+        loading: &mut LoadingAssets,
+    ) -> Result<BundleHandles, String> {
+        let bundle = self
+            .manifest
+            .bundles
+            .get(name)
+            .cloned()
+            .ok_or_else(|| format!("Asset manifest has no bundle named \"{}\"", name))?;
+
+        Ok(BundleHandles {
+            textures: load_all(phase, asset_server, loading, bundle.textures)?,
+            fonts: load_all(phase, asset_server, loading, bundle.fonts)?,
+            sounds: load_all(phase, asset_server, loading, bundle.sounds)?,
+        })
+    }
+}
+
+fn lookup<T: Asset>(handles: &HashMap<String, Handle<T>>, kind: &str, name: &str) -> Handle<T> {
+    handles.get(name).cloned().unwrap_or_else(|| {
+        error!("No loaded bundle provides a {} named \"{}\"", kind, name);
+        Handle::default()
+    })
+}
+
+fn load_all<T: Asset>(
+    phase: &str,
+    asset_server: &AssetServer,
+    loading: &mut LoadingAssets,
+    paths: HashMap<String, String>,
+) -> Result<HashMap<String, Handle<T>>, String> {
+    paths
+        .into_iter()
+        .map(|(name, path)| {
+            let handle = asset_server.load_untyped(path.as_str());
+            loading
+                .register(phase, handle.clone(), &path)
+                .map_err(|err| err.to_string())?;
+            Ok((name, handle.typed()))
+        })
+        .collect()
+}
+
+/// Reads and parses a RON file from the assets folder right away,
+/// for data that is needed before any asset can be looked up.
+pub fn read_ron_asset<T: DeserializeOwned>(
+    settings: Option<&AssetServerSettings>,
+    path: &str,
+) -> Result<T, String> {
+    let contents = read_text_asset(settings, path)?;
+    ron::from_str(&contents).map_err(|err| format!("{} ({})", path, err))
+}
+
+/// Reads a text file from the assets folder right away, see `read_ron_asset`.
+pub fn read_text_asset(
+    settings: Option<&AssetServerSettings>,
+    path: &str,
+) -> Result<String, String> {
+    let asset_folder = settings
+        .map(|settings| settings.asset_folder.clone())
+        .unwrap_or_else(|| AssetServerSettings::default().asset_folder);
+    let full_path = FileAssetIo::get_root_path().join(asset_folder).join(path);
+
+    std::fs::read_to_string(&full_path).map_err(|err| format!("{} ({})", full_path.display(), err))
+}
+
+fn load_game_assets(
+    asset_server: Res<AssetServer>,
+    settings: Option<Res<AssetServerSettings>>,
+    mut game_assets: ResMut<GameAssets>,
+    mut loading: ResMut<LoadingAssets>,
+) {
+    let result = loading
+        .begin_phase(BOOT_PHASE)
+        .map_err(|err| err.to_string())
+        .and_then(|_| read_ron_asset(settings.as_deref(), MANIFEST_PATH))
+        .and_then(|manifest| {
+            game_assets.manifest = manifest;
+            game_assets.load_bundle(BOOT_PHASE, BOOT_PHASE, &asset_server, &mut loading)
+        });
+
+    if let Err(err) = result {
+        // reported through the boot phase so the loading screen shows what went wrong
+        let _ = loading.fail(BOOT_PHASE, err);
+    }
+}
+
+fn load_bundle_system(
+    asset_server: Res<AssetServer>,
+    mut game_assets: ResMut<GameAssets>,
+    mut loading: ResMut<LoadingAssets>,
+    mut load_bundle_events: EventReader<LoadBundleEvent>,
+    mut phase_failed_events: EventWriter<PhaseFailedEvent>,
+) {
+    load_bundle_events.iter().for_each(|event| {
+        if let Err(err) = loading.begin_phase(&event.0) {
+            error!("Cannot load asset bundle \"{}\": {}", event.0, err);
+            phase_failed_events.send(PhaseFailedEvent(event.0.clone()));
+            return;
+        }
+
+        // the phase is dropped as failed by the loading manager, which reports it
+        match game_assets.load_handles(&event.0, &event.0, &asset_server, &mut loading) {
+            Ok(handles) => {
+                game_assets.staged.insert(event.0.clone(), handles);
+            }
+            Err(err) => {
+                let _ = loading.fail(&event.0, err);
+            }
+        }
+    });
+}
+
+/// Puts the assets of a bundle in place of the ones they replace once all of them are ready,
+/// or throws them away if the bundle fails to load.
+fn staged_bundle_system(
+    mut game_assets: ResMut<GameAssets>,
+    mut phase_loaded_events: EventReader<PhaseLoadedEvent>,
+    mut phase_failed_events: EventReader<PhaseFailedEvent>,
+) {
+    phase_loaded_events.iter().for_each(|event| {
+        if let Some(handles) = game_assets.staged.remove(&event.0) {
+            game_assets.handles.extend(handles);
+        }
+    });
+
+    phase_failed_events.iter().for_each(|event| {
+        game_assets.staged.remove(&event.0);
+    });
+}
@@ -1,18 +1,238 @@
-use bevy::prelude::*;
+use std::time::Duration;
+
+use bevy::{core::CoreSystem, prelude::*, render::camera::ScalingMode as CameraScalingMode};
+use rand::{rngs::StdRng, Rng, SeedableRng};
+use serde::{Deserialize, Serialize};
+
+use crate::profile::Profile;
 
 pub struct GameCorePlugin;
 
 impl Plugin for GameCorePlugin {
     fn build(&self, app: &mut App) {
-        app.add_startup_system(setup);
+        app.insert_resource(Playfield {
+            width: PLAYFIELD_WIDTH,
+            height: PLAYFIELD_HEIGHT,
+        })
+        // a clock inserted before the plugin, such as a manual one, is kept
+        .init_resource::<GameClock>()
+        .init_resource::<GameRng>()
+        .add_startup_system(setup)
+        .add_system_to_stage(CoreStage::First, game_clock_system.after(CoreSystem::Time))
+        .add_system_set(
+            GameSystem::Presentation
+                .set()
+                .with_system(playfield_scaling_system),
+        );
+    }
+}
+
+/// The phases of a frame within `CoreStage::Update`, run in the order listed. Every system of
+/// the game belongs to one of them, so events sent by a phase are seen by the later ones
+/// within the same frame.
+#[derive(SystemLabel, Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum GameSystem {
+    /// Turns the keyboard, mouse, gamepad and loading progress into events
+    Input,
+    /// Switches the game state and puts the run back on a new game or reset
+    State,
+    /// Moves the player and the pillars
+    Physics,
+    /// Checks the player against the pillars and the playfield bounds
+    Collision,
+    /// Settles the crossings and deaths of the frame, counts the score and ends the run
+    Scoring,
+    /// Spawns the screens and updates whatever is shown, played or saved
+    Presentation,
+}
+
+impl GameSystem {
+    const ORDER: [GameSystem; 6] = [
+        GameSystem::Input,
+        GameSystem::State,
+        GameSystem::Physics,
+        GameSystem::Collision,
+        GameSystem::Scoring,
+        GameSystem::Presentation,
+    ];
+
+    /// A system set running in this phase, after the phase before it.
+    pub fn set(self) -> SystemSet {
+        let set = SystemSet::new().label(self);
+
+        match Self::ORDER.iter().position(|phase| *phase == self) {
+            Some(index) if index > 0 => set.after(Self::ORDER[index - 1]),
+            _ => set,
+        }
+    }
+}
+
+/// The order of the systems within `GameSystem::Scoring`.
+#[derive(SystemLabel, Debug, Clone, PartialEq, Eq, Hash)]
+pub enum ResolveSystem {
+    /// Turns the candidates of the frame into crossings and at most one death
+    Run,
+    /// Counts the crossings into the score, before the death is acted upon
+    Score,
+}
+
+/// the playfield matches the background art
+const PLAYFIELD_WIDTH: f32 = 1280.0;
+const PLAYFIELD_HEIGHT: f32 = 720.0;
+
+/// large enough to cover whatever part of the window lies outside of the playfield
+const LETTERBOX_BAR_SIZE: f32 = 10000.0;
+
+/// The logical size of the game world, independent of the window size.
+/// All gameplay geometry is expressed in these units.
+pub struct Playfield {
+    pub width: f32,
+    pub height: f32,
+}
+
+/// How the playfield is mapped onto a window of a different aspect ratio.
+#[derive(Serialize, Deserialize, Clone, Copy, PartialEq, Debug)]
+pub enum ScalingMode {
+    /// Keep the aspect ratio and hide everything outside of the playfield.
+    Letterbox,
+    /// Keep the aspect ratio and show whatever lies outside of the playfield.
+    Fit,
+    /// Stretch the playfield to fill the window.
+    Stretch,
+}
+
+/// Values replacing the ones the pillars and the player are set up with, to try out how the
+/// game plays with them. Inserted before the plugins, as it is only read when they are set up.
+#[derive(Serialize, Deserialize, Default, Clone, Copy, PartialEq, Debug)]
+pub struct Tuning {
+    /// height of the opening between the top and bottom pillar, instead of the one of the
+    /// `Difficulty`
+    pub pillar_gap: Option<f32>,
+    /// instead of the one of the `Difficulty`
+    pub pillar_speed: Option<f32>,
+    /// instead of `PLAYER_GRAVITY`
+    pub player_gravity: Option<f32>,
+}
+
+/// The time the game runs on. It follows `Time`, unless it is stepped by hand or by a fixed
+/// step to run the game at a fixed rate regardless of the wall clock.
+#[derive(Default)]
+pub struct GameClock {
+    manual: bool,
+    /// length of every frame, for a clock that advances on its own at a fixed rate
+    step: Option<Duration>,
+    delta: Duration,
+    elapsed: Duration,
+}
+
+impl GameClock {
+    /// A clock that stands still until it is advanced.
+    pub fn manual() -> Self {
+        Self {
+            manual: true,
+            ..Default::default()
+        }
+    }
+
+    /// A clock that advances by `step` every frame, however long the frame really took.
+    pub fn fixed(step: Duration) -> Self {
+        Self {
+            step: Some(step),
+            ..Default::default()
+        }
+    }
+
+    /// Stops the clock following `Time`, to step it by hand, or lets it follow again.
+    pub fn set_manual(&mut self, manual: bool) {
+        self.manual = manual;
+    }
+
+    /// Moves a manual clock forward, `delta` being the length of the frames that follow.
+    pub fn advance(&mut self, delta: Duration) {
+        self.delta = delta;
+        self.elapsed += delta;
+    }
+
+    pub fn delta(&self) -> Duration {
+        self.delta
+    }
+
+    pub fn delta_seconds(&self) -> f32 {
+        self.delta.as_secs_f32()
+    }
+
+    pub fn seconds_since_startup(&self) -> f64 {
+        self.elapsed.as_secs_f64()
     }
 }
 
-fn setup(mut commands: Commands, mut windows: ResMut<Windows>) {
-    let window = windows.get_primary_mut().unwrap();
-    window.set_resizable(false);
+/// The randomness of the game. Every run draws its own seed from the generator of the session,
+/// so a run can be played again from its seed alone, and a seeded session from the first seed.
+pub struct GameRng {
+    session: StdRng,
+    next_run_seed: Option<u64>,
+    run_seed: u64,
+    run: StdRng,
+}
+
+impl Default for GameRng {
+    fn default() -> Self {
+        Self::from_session(StdRng::from_entropy())
+    }
+}
+
+impl GameRng {
+    pub fn seeded(seed: u64) -> Self {
+        Self::from_session(StdRng::seed_from_u64(seed))
+    }
+
+    fn from_session(session: StdRng) -> Self {
+        Self {
+            session,
+            next_run_seed: None,
+            run_seed: 0,
+            run: StdRng::seed_from_u64(0),
+        }
+    }
+
+    /// Makes the next run use this seed instead of drawing one, to play a recorded run again.
+    pub fn set_next_run_seed(&mut self, seed: u64) {
+        self.next_run_seed = Some(seed);
+    }
+
+    /// Starts the randomness of a new run, returning its seed.
+    pub fn begin_run(&mut self) -> u64 {
+        let session = &mut self.session;
+        let seed = self.next_run_seed.take().unwrap_or_else(|| session.gen());
+
+        self.run_seed = seed;
+        self.run = StdRng::seed_from_u64(seed);
+        seed
+    }
 
-    println!("Window size: {} {}", window.width(), window.height());
+    pub fn run_seed(&self) -> u64 {
+        self.run_seed
+    }
+
+    /// The generator of the current run.
+    pub fn run(&mut self) -> &mut StdRng {
+        &mut self.run
+    }
+}
+
+#[derive(Component)]
+struct GameCamera;
+
+#[derive(Component)]
+struct LetterboxBar;
+
+fn setup(mut commands: Commands, mut windows: ResMut<Windows>, playfield: Res<Playfield>) {
+    // there is no window when running headless
+    if let Some(window) = windows.get_primary_mut() {
+        window.set_resizable(true);
+
+        println!("Window size: {} {}", window.width(), window.height());
+    }
 
     let mut camera_bundle = OrthographicCameraBundle::new_2d();
 
@@ -20,6 +240,93 @@ fn setup(mut commands: Commands, mut windows: ResMut<Windows>) {
     // we need to use z: -1 for the background, so shift the camera a bit more forward
     camera_bundle.transform.translation.z = 500.0;
 
-    commands.spawn_bundle(camera_bundle);
+    // the projection is sized by playfield_scaling_system instead
+    camera_bundle.orthographic_projection.scaling_mode = CameraScalingMode::None;
+
+    commands.spawn_bundle(camera_bundle).insert(GameCamera);
     commands.spawn_bundle(UiCameraBundle::default());
+
+    // one bar on each side of the playfield, only shown when letterboxing
+    let offset_x = (playfield.width + LETTERBOX_BAR_SIZE) / 2.0;
+    let offset_y = (playfield.height + LETTERBOX_BAR_SIZE) / 2.0;
+
+    [
+        Vec2::new(-offset_x, 0.0),
+        Vec2::new(offset_x, 0.0),
+        Vec2::new(0.0, -offset_y),
+        Vec2::new(0.0, offset_y),
+    ]
+    .into_iter()
+    .for_each(|position| {
+        commands
+            .spawn_bundle(SpriteBundle {
+                sprite: Sprite {
+                    color: Color::BLACK,
+                    custom_size: Some(Vec2::new(LETTERBOX_BAR_SIZE, LETTERBOX_BAR_SIZE)),
+                    ..Default::default()
+                },
+                transform: Transform {
+                    // above the game, but still within the camera's clipping range
+                    translation: position.extend(400.0),
+                    ..Default::default()
+                },
+                ..Default::default()
+            })
+            .insert(LetterboxBar);
+    });
+}
+
+fn game_clock_system(time: Res<Time>, mut clock: ResMut<GameClock>) {
+    if let Some(step) = clock.step {
+        clock.advance(step);
+    } else if !clock.manual {
+        clock.delta = time.delta();
+        clock.elapsed = time.time_since_startup();
+    }
+}
+
+fn playfield_scaling_system(
+    windows: Res<Windows>,
+    playfield: Res<Playfield>,
+    profile: Res<Profile>,
+    mut camera_query: Query<&mut OrthographicProjection, With<GameCamera>>,
+    mut bar_query: Query<&mut Visibility, With<LetterboxBar>>,
+) {
+    // a minimized window has no size to scale to
+    let window = match windows.get_primary() {
+        Some(window) if window.width() > 0.0 && window.height() > 0.0 => window,
+        _ => return,
+    };
+
+    let (half_width, half_height) = (playfield.width / 2.0, playfield.height / 2.0);
+    let window_aspect = window.width() / window.height();
+    let playfield_aspect = playfield.width / playfield.height;
+
+    let (half_width, half_height) = match profile.scaling {
+        ScalingMode::Stretch => (half_width, half_height),
+        ScalingMode::Letterbox | ScalingMode::Fit => {
+            if window_aspect > playfield_aspect {
+                (half_height * window_aspect, half_height)
+            } else {
+                (half_width, half_width / window_aspect)
+            }
+        }
+    };
+
+    camera_query.iter_mut().for_each(|mut projection| {
+        // only write when needed, to not trigger change detection every frame
+        if projection.right != half_width || projection.top != half_height {
+            projection.left = -half_width;
+            projection.right = half_width;
+            projection.bottom = -half_height;
+            projection.top = half_height;
+        }
+    });
+
+    bar_query.iter_mut().for_each(|mut visibility| {
+        let letterbox = profile.scaling == ScalingMode::Letterbox;
+        if visibility.is_visible != letterbox {
+            visibility.is_visible = letterbox;
+        }
+    });
 }
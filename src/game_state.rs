@@ -1,6 +1,10 @@
 use bevy::prelude::*;
 
-use crate::{loading::FinishLoadingEvent, player::PlayerKilledEvent};
+use crate::{
+    game_core::{GameRng, GameSystem, ResolveSystem},
+    loading::FinishLoadingEvent,
+    player::PlayerKilledEvent,
+};
 
 pub struct GameStatePlugin;
 
@@ -9,23 +13,72 @@ impl Plugin for GameStatePlugin {
         app.insert_resource(GameState(GameStateType::Loading))
             .add_event::<StartNewGameEvent>()
             .add_event::<OnGameStateChangedEvent>()
-            .add_system(finish_loading_system)
-            .add_system(start_new_system)
-            .add_system(game_over_system);
+            .add_event::<OpenMenuEvent>()
+            .add_event::<ResetGameEvent>()
+            .add_event::<BeginPlayEvent>()
+            .add_system_set(
+                GameSystem::State
+                    .set()
+                    .label(GameStateSystem::Transition)
+                    .with_system(finish_loading_system)
+                    .with_system(open_menu_system)
+                    .with_system(start_new_system)
+                    .with_system(begin_play_system),
+            )
+            // the run ends in the frame the death is resolved, once its score is counted
+            .add_system_set(
+                GameSystem::Scoring
+                    .set()
+                    .with_system(game_over_system.after(ResolveSystem::Score)),
+            );
     }
 }
 
+#[derive(SystemLabel, Debug, Clone, PartialEq, Eq, Hash)]
+pub enum GameStateSystem {
+    /// Switches the state on the events of the input phase, before the run is put back
+    Transition,
+}
+
 pub struct StartNewGameEvent;
+/// Ends the get ready countdown, letting gravity and the pillars take over.
+pub struct BeginPlayEvent {
+    /// the countdown was cut short by a tap, which counts as the first flap
+    pub flap: bool,
+}
+#[derive(Clone)]
 pub struct OnGameStateChangedEvent(pub GameStateType);
+/// Switches between the menu screens, i.e. `StartScreen`, `Settings`, `HighScores` and `Stats`.
+/// The game over screen may also return to the menus, and so may a run in progress, which is
+/// abandoned.
+pub struct OpenMenuEvent(pub GameStateType);
+/// Clears the last run, putting the player, pillars and score back in their idle state.
+pub struct ResetGameEvent;
 
-#[derive(Clone, Copy)]
+#[derive(Clone, Copy, Debug, PartialEq)]
 pub enum GameStateType {
     Loading,
     StartScreen,
+    Settings,
+    HighScores,
+    Stats,
+    GetReady,
     Playing,
     GameOver,
 }
 
+impl GameStateType {
+    pub fn is_menu(&self) -> bool {
+        matches!(
+            self,
+            GameStateType::StartScreen
+                | GameStateType::Settings
+                | GameStateType::HighScores
+                | GameStateType::Stats
+        )
+    }
+}
+
 pub struct GameState(pub GameStateType);
 
 // TODO: Provide this as methods of GameStateType?
@@ -33,6 +86,10 @@ pub fn is_playing(game_state: &Res<GameState>) -> bool {
     matches!(game_state.0, GameStateType::Playing)
 }
 
+pub fn is_get_ready(game_state: &Res<GameState>) -> bool {
+    matches!(game_state.0, GameStateType::GetReady)
+}
+
 pub fn is_game_over(game_state: &Res<GameState>) -> bool {
     matches!(game_state.0, GameStateType::GameOver)
 }
@@ -68,30 +125,76 @@ fn finish_loading_system(
     });
 }
 
+fn open_menu_system(
+    mut game_status: ResMut<GameState>,
+    mut open_menu_events: EventReader<OpenMenuEvent>,
+    mut on_change_event: EventWriter<OnGameStateChangedEvent>,
+    mut reset_events: EventWriter<ResetGameEvent>,
+) {
+    open_menu_events.iter().for_each(|event| {
+        let in_run = matches!(
+            game_status.0,
+            GameStateType::GetReady | GameStateType::Playing | GameStateType::GameOver
+        );
+        if (game_status.0.is_menu() || in_run) && event.0.is_menu() {
+            if in_run {
+                reset_events.send(ResetGameEvent);
+            }
+            update_game_state(event.0, &mut game_status, &mut on_change_event);
+        }
+    });
+}
+
 fn start_new_system(
     mut game_status: ResMut<GameState>,
+    mut rng: ResMut<GameRng>,
     mut start_events: EventReader<StartNewGameEvent>,
     mut on_change_event: EventWriter<OnGameStateChangedEvent>,
 ) {
     start_events.iter().for_each(|_| {
+        rng.begin_run();
+
         update_game_state(
-            GameStateType::Playing,
+            GameStateType::GetReady,
             &mut game_status,
             &mut on_change_event,
         );
     });
 }
 
+fn begin_play_system(
+    mut game_status: ResMut<GameState>,
+    mut begin_play_events: EventReader<BeginPlayEvent>,
+    mut on_change_event: EventWriter<OnGameStateChangedEvent>,
+) {
+    begin_play_events.iter().for_each(|_| {
+        if matches!(game_status.0, GameStateType::GetReady) {
+            update_game_state(
+                GameStateType::Playing,
+                &mut game_status,
+                &mut on_change_event,
+            );
+        }
+    });
+}
+
 fn game_over_system(
     mut game_status: ResMut<GameState>,
     mut killed_events: EventReader<PlayerKilledEvent>,
     mut on_change_event: EventWriter<OnGameStateChangedEvent>,
 ) {
-    killed_events.iter().for_each(|_| {
-        update_game_state(
-            GameStateType::GameOver,
-            &mut game_status,
-            &mut on_change_event,
-        );
+    killed_events.iter().for_each(|event| {
+        // only a run being played can end
+        if matches!(game_status.0, GameStateType::Playing) {
+            info!(
+                "Player killed by {:?} (pillar {:?}) at {} after {:.1}s",
+                event.cause, event.pillar, event.position, event.run_time
+            );
+            update_game_state(
+                GameStateType::GameOver,
+                &mut game_status,
+                &mut on_change_event,
+            );
+        }
     });
 }
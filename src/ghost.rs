@@ -0,0 +1,359 @@
+use std::{collections::HashMap, time::Duration};
+
+use bevy::prelude::*;
+
+use crate::{
+    game_assets::GameAssets,
+    game_core::{GameClock, GameRng, GameSystem, Tuning},
+    game_state::{GameState, GameStateSystem, StartNewGameEvent},
+    locale::LocalizedText,
+    mover::{Mover, MoverSystem},
+    pillars::{Difficulty, Pillar, PILLAR_WIDTH},
+    player::{hover_y, player_gravity, PlayerControl, LEAP_Y_VELOCITY},
+    profile::{save_path, SaveSettings},
+    replay::{Replay, RunRecordedEvent},
+    theme::ThemedSprite,
+    widgets::{self, UiTheme},
+};
+
+/// Races a translucent bird against the player while `GhostSettings::enabled` is set. The ghost
+/// flies the taps of the best run so far, or of a loaded replay, through the same pillars, and
+/// goes through anything it hits.
+pub struct GhostPlugin;
+
+impl Plugin for GhostPlugin {
+    fn build(&self, app: &mut App) {
+        let settings = app
+            .world
+            .get_resource_or_insert_with(SaveSettings::default)
+            .clone();
+        let difficulty = *app.world.get_resource_or_insert_with(Difficulty::default);
+
+        app.init_resource::<GhostSettings>()
+            .insert_resource(BestRun::load(&settings, difficulty))
+            .insert_resource(GhostRun::default())
+            .add_startup_system(setup_ghost)
+            .add_system_set(
+                GameSystem::State
+                    .set()
+                    .with_system(start_race_system.before(GameStateSystem::Transition)),
+            )
+            .add_system_set(
+                GameSystem::Physics
+                    .set()
+                    .with_system(ghost_flight_system.after(MoverSystem::Move)),
+            )
+            .add_system_set(
+                GameSystem::Presentation
+                    .set()
+                    .with_system(best_run_system)
+                    .with_system(ghost_sprite_system)
+                    .with_system(ghost_score_system),
+            );
+    }
+}
+
+/// Inserted before the plugin to race from the first run, or changed at any time. Only runs
+/// started by the player are raced.
+#[derive(Default)]
+pub struct GhostSettings {
+    pub enabled: bool,
+    /// the run to race instead of the best one so far
+    pub replay: Option<Replay>,
+}
+
+/// The best run played on the difficulty of the game, kept in the save folder.
+pub struct BestRun(pub Option<Replay>);
+
+impl BestRun {
+    fn file_name(difficulty: Difficulty) -> String {
+        format!("best_run_{:?}.ron", difficulty).to_lowercase()
+    }
+
+    fn load(settings: &SaveSettings, difficulty: Difficulty) -> Self {
+        let path = match save_path(settings, &Self::file_name(difficulty)) {
+            Some(path) if path.exists() => path,
+            _ => return BestRun(None),
+        };
+
+        match Replay::load(&path) {
+            Ok(replay) => BestRun(Some(replay)),
+            Err(err) => {
+                error!("Ignoring unreadable best run {}: {}", path.display(), err);
+                BestRun(None)
+            }
+        }
+    }
+}
+
+const GHOST_ALPHA: f32 = 0.4;
+/// Behind the player and the pillars, in front of the background.
+const GHOST_Z: f32 = -0.5;
+/// Distance from the top of the window to the ghost score, just below the live score.
+const GHOST_SCORE_TOP: f32 = 140.0;
+
+/// The translucent bird flying the run raced, shown while it is in the air.
+#[derive(Component)]
+pub struct Ghost;
+
+#[derive(Component)]
+struct GhostScoreOverlay;
+
+/// The ghost score as last shown.
+#[derive(Component)]
+struct GhostScoreLabel(u32);
+
+enum GhostFlight {
+    /// the countdown of the live run is not over yet
+    Waiting,
+    Flying,
+    /// the frames of the run raced are all flown
+    Over,
+}
+
+impl Default for GhostFlight {
+    fn default() -> Self {
+        GhostFlight::Waiting
+    }
+}
+
+/// The race against the current run, if any.
+#[derive(Default)]
+struct GhostRun {
+    replay: Option<Replay>,
+    flight: GhostFlight,
+    /// index of the frame of the replay to fly next
+    frame: usize,
+    /// nanoseconds played by the live run that the ghost has yet to fly
+    owed: u64,
+    y: f32,
+    velocity: f32,
+    score: u32,
+    /// whether each pillar was past the ghost the last time it moved
+    passed: HashMap<Entity, bool>,
+}
+
+fn setup_ghost(mut commands: Commands, game_assets: Res<GameAssets>) {
+    commands
+        .spawn_bundle(SpriteBundle {
+            sprite: Sprite {
+                color: Color::rgba(1.0, 1.0, 1.0, GHOST_ALPHA),
+                ..Default::default()
+            },
+            texture: game_assets.texture("player"),
+            transform: Transform::from_xyz(0.0, 0.0, GHOST_Z),
+            visibility: Visibility { is_visible: false },
+            ..Default::default()
+        })
+        .insert(Ghost)
+        .insert(ThemedSprite("player"));
+}
+
+/// Picks the run to race when the player starts a new one, which is then drawn from the same
+/// seed. Runs before the transition draws the seed of the new run.
+fn start_race_system(
+    settings: Res<GhostSettings>,
+    best_run: Res<BestRun>,
+    difficulty: Res<Difficulty>,
+    control: Res<PlayerControl>,
+    mut rng: ResMut<GameRng>,
+    mut run: ResMut<GhostRun>,
+    mut start_new_events: EventReader<StartNewGameEvent>,
+) {
+    if start_new_events.iter().count() == 0 {
+        return;
+    }
+
+    *run = GhostRun::default();
+
+    if !settings.enabled || control.scripted {
+        return;
+    }
+
+    let replay = match settings.replay.as_ref().or(best_run.0.as_ref()) {
+        Some(replay) if replay.difficulty != *difficulty => {
+            warn!(
+                "The ghost run was played on {:?} but the game is set to {:?}, racing without it",
+                replay.difficulty, *difficulty
+            );
+            return;
+        }
+        // recorded before play began
+        Some(replay) if replay.play_frame.is_none() => return,
+        Some(replay) => replay,
+        None => return,
+    };
+
+    rng.set_next_run_seed(replay.run_seed);
+    run.replay = Some(replay.clone());
+}
+
+/// Flies the ghost through the frames of its run for as long as the live run has been played,
+/// the way the player flies, and counts the pillars it passes.
+fn ghost_flight_system(
+    time: Res<GameClock>,
+    tuning: Res<Tuning>,
+    game_state: Res<GameState>,
+    mut run: ResMut<GhostRun>,
+    pillar_query: Query<(Entity, &Transform, &Mover), With<Pillar>>,
+) {
+    if !crate::game_state::is_playing(&game_state) {
+        return;
+    }
+
+    let run = &mut *run;
+    let gravity = player_gravity(&tuning);
+    let replay = match &run.replay {
+        Some(replay) => replay,
+        None => return,
+    };
+
+    if matches!(run.flight, GhostFlight::Waiting) {
+        // play begins where the countdown left the bobbing bird
+        let play_frame = replay.play_frame.unwrap_or(0);
+        let hover_time: f64 = replay.frames[..play_frame]
+            .iter()
+            .map(|frame| Duration::from_nanos(frame.delta).as_secs_f64())
+            .sum();
+
+        run.flight = GhostFlight::Flying;
+        run.frame = play_frame;
+        run.y = hover_y(hover_time);
+        run.velocity = 0.0;
+    }
+
+    if !matches!(run.flight, GhostFlight::Flying) {
+        return;
+    }
+
+    run.owed += time.delta().as_nanos() as u64;
+    while let Some(frame) = replay.frames.get(run.frame) {
+        if frame.delta > run.owed {
+            break;
+        }
+        run.owed -= frame.delta;
+        run.frame += 1;
+
+        let delta = Duration::from_nanos(frame.delta).as_secs_f32();
+        if frame.tap {
+            run.velocity = LEAP_Y_VELOCITY;
+        }
+        run.velocity += -gravity * delta;
+        run.y += run.velocity * delta;
+    }
+
+    // passes in the frame the run raced ended in still count, as they do for the player
+    pillar_query.for_each(|(entity, transform, mover)| {
+        let passed = mover.active && transform.translation.x < -(PILLAR_WIDTH / 4.0);
+        let was_passed = run.passed.insert(entity, passed).unwrap_or(false);
+        if passed && !was_passed {
+            run.score += 1;
+        }
+    });
+
+    if run.frame >= replay.frames.len() {
+        run.flight = GhostFlight::Over;
+    }
+}
+
+/// Keeps the best run the player played to the end, to race it later.
+fn best_run_system(
+    settings: Res<SaveSettings>,
+    mut best_run: ResMut<BestRun>,
+    mut recorded_events: EventReader<RunRecordedEvent>,
+) {
+    recorded_events.iter().for_each(|event| {
+        let better = best_run
+            .0
+            .as_ref()
+            .map_or(true, |best| event.replay.score > best.score);
+        if event.scripted || !better {
+            return;
+        }
+
+        best_run.0 = Some(event.replay.clone());
+
+        if let Some(path) = save_path(&settings, &BestRun::file_name(event.replay.difficulty)) {
+            if let Err(err) = event.replay.save(&path) {
+                error!("Cannot save best run {}: {}", path.display(), err);
+            }
+        }
+    });
+}
+
+fn ghost_sprite_system(
+    game_state: Res<GameState>,
+    run: Res<GhostRun>,
+    mut query: Query<(&mut Transform, &mut Visibility), With<Ghost>>,
+) {
+    let (mut transform, mut visibility) = query.single_mut();
+    let visible =
+        crate::game_state::is_playing(&game_state) && matches!(run.flight, GhostFlight::Flying);
+
+    // only write when needed, to not trigger change detection every frame
+    if visibility.is_visible != visible {
+        visibility.is_visible = visible;
+    }
+    if transform.translation.y != run.y {
+        transform.translation.y = run.y;
+    }
+}
+
+/// Shows the score of the ghost under the live score while a run is raced.
+fn ghost_score_system(
+    mut commands: Commands,
+    ui_theme: Res<UiTheme>,
+    game_state: Res<GameState>,
+    run: Res<GhostRun>,
+    overlay_query: Query<Entity, With<GhostScoreOverlay>>,
+    mut label_query: Query<(&mut GhostScoreLabel, &mut LocalizedText)>,
+) {
+    // the results panel takes over once the run is over, as with the live score
+    let racing = run.replay.is_some()
+        && (crate::game_state::is_playing(&game_state)
+            || crate::game_state::is_get_ready(&game_state));
+
+    let shown = overlay_query.iter().next().is_some();
+    if racing != shown {
+        overlay_query
+            .iter()
+            .for_each(|entity| commands.entity(entity).despawn_recursive());
+
+        if racing {
+            commands
+                .spawn_bundle(NodeBundle {
+                    style: Style {
+                        position_type: PositionType::Absolute,
+                        size: Size::new(Val::Percent(100.0), Val::Percent(100.0)),
+                        flex_direction: FlexDirection::ColumnReverse,
+                        justify_content: JustifyContent::FlexStart,
+                        align_items: AlignItems::Center,
+                        padding: Rect {
+                            top: Val::Px(GHOST_SCORE_TOP),
+                            ..Default::default()
+                        },
+                        ..Default::default()
+                    },
+                    color: UiColor(Color::NONE),
+                    ..Default::default()
+                })
+                .insert(GhostScoreOverlay)
+                .with_children(|parent| {
+                    widgets::label(
+                        parent,
+                        &ui_theme,
+                        LocalizedText::new("ghost-score").with_arg("score", run.score),
+                    )
+                    .insert(GhostScoreLabel(run.score));
+                });
+        }
+        return;
+    }
+
+    label_query.iter_mut().for_each(|(mut label, mut text)| {
+        if label.0 != run.score {
+            label.0 = run.score;
+            text.set_arg("score", run.score);
+        }
+    });
+}
@@ -0,0 +1,78 @@
+use std::time::{Duration, Instant};
+
+use bevy::{
+    app::PluginGroupBuilder, asset::AssetPlugin, core::CorePlugin, prelude::*,
+    render::texture::ImageTextureLoader, text::FontLoader,
+};
+
+use crate::{
+    audio::AudioSettings,
+    game_core::GameClock,
+    game_state::{GameState, GameStateType},
+    profile::SaveSettings,
+    FlappyGamePlugins,
+};
+
+/// What the game needs of Bevy's `DefaultPlugins` when there is no window, renderer or input
+/// device, in place of them. The app still has to be driven, by a `ScheduleRunnerPlugin` or
+/// by calling `App::update`.
+pub struct HeadlessPlugins;
+
+impl PluginGroup for HeadlessPlugins {
+    fn build(&mut self, group: &mut PluginGroupBuilder) {
+        group
+            .add(CorePlugin)
+            .add(AssetPlugin)
+            .add(HeadlessResourcesPlugin);
+    }
+}
+
+/// The assets and resources the rendering, text, window and input plugins would otherwise
+/// provide.
+struct HeadlessResourcesPlugin;
+
+impl Plugin for HeadlessResourcesPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_asset::<Image>()
+            .init_asset_loader::<ImageTextureLoader>()
+            .add_asset::<Font>()
+            .init_asset_loader::<FontLoader>()
+            .add_asset::<TextureAtlas>()
+            .init_resource::<Windows>()
+            .init_resource::<ClearColor>()
+            .init_resource::<Input<KeyCode>>()
+            .init_resource::<Input<MouseButton>>()
+            .init_resource::<Input<GamepadButton>>();
+    }
+}
+
+/// The boot assets load on background threads, so they are waited for in wall time.
+const LOADING_TIMEOUT: Duration = Duration::from_secs(10);
+
+/// The whole game without a window, sound or save files, on a clock that only moves when
+/// advanced, returned once the boot assets are loaded and the start screen is up. `configure`
+/// is called before the plugins are added, to insert the resources they read or add systems
+/// that should see the boot. Panics if the assets do not load.
+pub fn headless_app(configure: impl FnOnce(&mut App)) -> App {
+    let mut app = App::new();
+
+    app.insert_resource(GameClock::manual())
+        .insert_resource(SaveSettings { folder: None })
+        .insert_resource(AudioSettings { muted: true });
+    configure(&mut app);
+    app.add_plugins(HeadlessPlugins)
+        .add_plugins(FlappyGamePlugins::default());
+
+    let started = Instant::now();
+    while app.world.get_resource::<GameState>().unwrap().0 == GameStateType::Loading {
+        assert!(
+            started.elapsed() < LOADING_TIMEOUT,
+            "the boot assets did not load within {:?}",
+            LOADING_TIMEOUT
+        );
+        std::thread::sleep(Duration::from_millis(1));
+        app.update();
+    }
+
+    app
+}
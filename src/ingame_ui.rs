@@ -1,69 +1,166 @@
 use bevy::prelude::*;
 
-use crate::score::ScoreUpdatedEvent;
+use crate::{
+    game_assets::GameAssets,
+    game_core::{GameClock, GameSystem, Playfield},
+    game_state::GameState,
+    score::ScoreUpdatedEvent,
+    widgets::UiTheme,
+};
 
 pub struct IngameUiPlugin;
 
 impl Plugin for IngameUiPlugin {
     fn build(&self, app: &mut App) {
-        app.add_startup_system(setup_ingame_ui)
-            .add_system(score_ui_update_system);
+        app.add_startup_system(setup_ingame_ui).add_system_set(
+            GameSystem::Presentation
+                .set()
+                .with_system(score_display_update_system)
+                .with_system(score_display_animation_system),
+        );
     }
 }
 
+const DIGIT_WIDTH: f32 = 48.0;
+const DIGIT_HEIGHT: f32 = 72.0;
+const DIGIT_ADVANCE: f32 = 44.0;
+const MAX_DIGITS: usize = 5;
+/// Scores past this are shown as this, rather than losing their leading digits.
+const MAX_SHOWN_SCORE: u32 = 10u32.pow(MAX_DIGITS as u32) - 1;
+const TOP_MARGIN: f32 = 90.0;
+
+const POP_DURATION: f32 = 0.25;
+const POP_SCALE: f32 = 0.4;
+const MILESTONE: u32 = 10;
+const FLASH_DURATION: f32 = 0.8;
+const FLASH_PERIOD: f32 = 0.1;
+
+/// The large score at the top center of the playfield, drawn one sprite per digit.
+#[derive(Component)]
+struct ScoreDisplay {
+    /// how many of the digit sprites are in use
+    length: usize,
+    /// time since the last increment, drives the pop
+    pop: f32,
+    /// time since the last milestone, drives the flash
+    flash: f32,
+}
+
 #[derive(Component)]
-struct ScoreText;
+struct ScoreDigit(usize);
 
-fn setup_ingame_ui(mut commands: Commands, asset_server: Res<AssetServer>) {
-    let font = asset_server.load("FiraSans-Bold.ttf");
+fn setup_ingame_ui(
+    mut commands: Commands,
+    game_assets: Res<GameAssets>,
+    playfield: Res<Playfield>,
+    mut texture_atlases: ResMut<Assets<TextureAtlas>>,
+) {
+    let digits = texture_atlases.add(TextureAtlas::from_grid(
+        game_assets.texture("digits"),
+        Vec2::new(DIGIT_WIDTH, DIGIT_HEIGHT),
+        10,
+        1,
+    ));
 
     commands
-        .spawn_bundle(TextBundle {
-            text: Text {
-                sections: vec![
-                    TextSection {
-                        value: "Score: ".to_string(),
-                        style: TextStyle {
-                            font: font.clone(),
-                            font_size: 30.0,
-                            color: Color::BLACK,
-                        },
-                    },
-                    TextSection {
-                        value: "0".to_string(),
-                        style: TextStyle {
-                            font,
-                            font_size: 30.0,
-                            color: Color::BLACK,
-                        },
-                    },
-                ],
-                alignment: TextAlignment {
-                    vertical: VerticalAlign::Center,
-                    horizontal: HorizontalAlign::Center,
-                },
-            },
-            style: Style {
-                position_type: PositionType::Absolute,
-                position: Rect {
-                    top: Val::Px(15.0),
-                    left: Val::Px(15.0),
-                    ..Default::default()
-                },
-                ..Default::default()
-            },
+        .spawn()
+        .insert(ScoreDisplay {
+            length: 1,
+            pop: POP_DURATION,
+            flash: FLASH_DURATION,
+        })
+        .insert(Transform {
+            // in front of the pillars, behind the letterbox bars
+            translation: Vec3::new(0.0, playfield.height / 2.0 - TOP_MARGIN, 300.0),
             ..Default::default()
         })
-        .insert(ScoreText);
+        .insert(GlobalTransform::default())
+        .with_children(|parent| {
+            (0..MAX_DIGITS).for_each(|index| {
+                parent
+                    .spawn_bundle(SpriteSheetBundle {
+                        texture_atlas: digits.clone(),
+                        visibility: Visibility { is_visible: false },
+                        ..Default::default()
+                    })
+                    .insert(ScoreDigit(index));
+            });
+        });
 }
 
-fn score_ui_update_system(
-    mut query: Query<&mut Text, With<ScoreText>>,
+fn score_display_update_system(
+    mut display_query: Query<&mut ScoreDisplay>,
+    mut digit_query: Query<(&ScoreDigit, &mut TextureAtlasSprite, &mut Transform)>,
     mut score_updated_events: EventReader<ScoreUpdatedEvent>,
 ) {
-    let mut text = query.single_mut();
+    let score = match score_updated_events.iter().last() {
+        Some(event) => event.0,
+        None => return,
+    };
+
+    let digits: Vec<usize> = score
+        .min(MAX_SHOWN_SCORE)
+        .to_string()
+        .chars()
+        .filter_map(|digit| digit.to_digit(10))
+        .map(|digit| digit as usize)
+        .collect();
+    let offset = (digits.len() as f32 - 1.0) * DIGIT_ADVANCE / 2.0;
+
+    let mut display = display_query.single_mut();
+    display.length = digits.len();
+    if score > 0 {
+        display.pop = 0.0;
+    }
+    if score > 0 && score % MILESTONE == 0 {
+        display.flash = 0.0;
+    }
+
+    digit_query
+        .iter_mut()
+        .for_each(|(digit, mut sprite, mut transform)| {
+            if let Some(value) = digits.get(digit.0) {
+                sprite.index = *value;
+                transform.translation.x = digit.0 as f32 * DIGIT_ADVANCE - offset;
+            }
+        });
+}
+
+fn score_display_animation_system(
+    time: Res<GameClock>,
+    game_state: Res<GameState>,
+    ui_theme: Res<UiTheme>,
+    mut display_query: Query<(&mut ScoreDisplay, &mut Transform)>,
+    mut digit_query: Query<
+        (&ScoreDigit, &mut TextureAtlasSprite, &mut Visibility),
+        Without<ScoreDisplay>,
+    >,
+) {
+    let (mut display, mut transform) = display_query.single_mut();
+    let delta = time.delta_seconds();
+
+    display.pop = (display.pop + delta).min(POP_DURATION);
+    display.flash = (display.flash + delta).min(FLASH_DURATION);
+
+    // jumps up in size on each point and eases back down
+    let pop = 1.0 - display.pop / POP_DURATION;
+    transform.scale = Vec3::splat(1.0 + POP_SCALE * pop * pop);
+
+    let flashing = display.flash < FLASH_DURATION && (display.flash / FLASH_PERIOD) as u32 % 2 == 0;
+    let color = if flashing {
+        ui_theme.highlight_color
+    } else {
+        Color::WHITE
+    };
+
+    // the results panel takes over showing the score once the run is over
+    let shown =
+        crate::game_state::is_playing(&game_state) || crate::game_state::is_get_ready(&game_state);
 
-    score_updated_events.iter().for_each(|event| {
-        text.sections[1].value = event.0.to_string();
-    });
+    digit_query
+        .iter_mut()
+        .for_each(|(digit, mut sprite, mut visibility)| {
+            visibility.is_visible = shown && digit.0 < display.length;
+            sprite.color = color;
+        });
 }
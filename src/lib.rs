@@ -0,0 +1,143 @@
+pub mod attract;
+pub mod audio;
+pub mod autopilot;
+pub mod background;
+pub mod debug_schedule;
+pub mod env;
+pub mod game_assets;
+pub mod game_core;
+pub mod game_state;
+pub mod ghost;
+pub mod headless;
+pub mod ingame_ui;
+pub mod loading;
+pub mod locale;
+pub mod mover;
+pub mod pillars;
+pub mod player;
+pub mod profile;
+pub mod replay;
+pub mod score;
+pub mod screen_end;
+pub mod screen_get_ready;
+pub mod screen_high_scores;
+pub mod screen_settings;
+pub mod screen_start;
+pub mod screen_stats;
+pub mod stats;
+#[cfg(test)]
+mod testing;
+pub mod theme;
+pub mod widgets;
+
+use bevy::{app::PluginGroupBuilder, prelude::*};
+
+pub use attract::AttractPlugin;
+pub use audio::GameAudioPlugin;
+pub use autopilot::AutopilotPlugin;
+pub use background::BackgroundPlugin;
+pub use game_assets::GameAssetsPlugin;
+pub use game_core::GameCorePlugin;
+pub use game_state::GameStatePlugin;
+pub use ghost::GhostPlugin;
+pub use headless::HeadlessPlugins;
+pub use ingame_ui::IngameUiPlugin;
+pub use loading::LoadingManagerPlugin;
+pub use locale::LocalePlugin;
+pub use mover::MoverPlugin;
+pub use pillars::PillarsPlugin;
+pub use player::PlayerPlugin;
+pub use profile::ProfilePlugin;
+pub use replay::ReplayPlugin;
+pub use score::ScorePlugin;
+pub use screen_end::ScreenEndPlugin;
+pub use screen_get_ready::ScreenGetReadyPlugin;
+pub use screen_high_scores::ScreenHighScoresPlugin;
+pub use screen_settings::ScreenSettingsPlugin;
+pub use screen_start::ScreenStartPlugin;
+pub use screen_stats::ScreenStatsPlugin;
+pub use stats::StatsPlugin;
+pub use theme::ThemePlugin;
+pub use widgets::WidgetsPlugin;
+
+/// Every plugin of the game, for an app that already has Bevy's `DefaultPlugins` or the
+/// equivalent resources. The optional pieces can be left out with the `without_*` methods,
+/// and any single plugin with `App::add_plugins_with` and `disable`.
+pub struct FlappyGamePlugins {
+    audio_backend: bool,
+    background: bool,
+    score_display: bool,
+}
+
+impl Default for FlappyGamePlugins {
+    fn default() -> Self {
+        Self {
+            audio_backend: true,
+            background: true,
+            score_display: true,
+        }
+    }
+}
+
+impl FlappyGamePlugins {
+    /// Leaves out `bevy_kira_audio::AudioPlugin`, for apps that add it themselves.
+    pub fn without_audio_backend(mut self) -> Self {
+        self.audio_backend = false;
+        self
+    }
+
+    /// Leaves out the background sprite, keeping the clear colour of the theme.
+    pub fn without_background(mut self) -> Self {
+        self.background = false;
+        self
+    }
+
+    /// Leaves out the large score shown while playing.
+    pub fn without_score_display(mut self) -> Self {
+        self.score_display = false;
+        self
+    }
+}
+
+impl PluginGroup for FlappyGamePlugins {
+    fn build(&mut self, group: &mut PluginGroupBuilder) {
+        group
+            .add(GameCorePlugin)
+            .add(GameAssetsPlugin)
+            .add(ProfilePlugin)
+            .add(ThemePlugin)
+            .add(LocalePlugin)
+            .add(WidgetsPlugin)
+            .add(ScorePlugin)
+            .add(StatsPlugin)
+            .add(GameStatePlugin)
+            .add(PlayerPlugin);
+
+        if self.audio_backend {
+            group.add(bevy_kira_audio::AudioPlugin);
+        }
+        group.add(GameAudioPlugin).add(LoadingManagerPlugin);
+
+        if self.score_display {
+            group.add(IngameUiPlugin);
+        }
+
+        group
+            .add(MoverPlugin)
+            .add(PillarsPlugin)
+            .add(ScreenStartPlugin)
+            .add(ScreenSettingsPlugin)
+            .add(ScreenHighScoresPlugin)
+            .add(ScreenStatsPlugin)
+            .add(ScreenGetReadyPlugin)
+            .add(ScreenEndPlugin)
+            .add(ReplayPlugin)
+            .add(GhostPlugin)
+            .add(AutopilotPlugin)
+            .add(AttractPlugin);
+
+        if self.background {
+            group.add(BackgroundPlugin);
+        }
+    }
+}
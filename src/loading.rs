@@ -1,91 +1,332 @@
-use bevy::prelude::*;
+use std::fmt;
 
-use crate::game_state::GameState;
+use bevy::{asset::LoadState, prelude::*};
 
-pub struct LoadingAssets(pub Vec<HandleUntyped>);
+use crate::{
+    game_core::GameSystem,
+    game_state::GameState,
+    locale::LocalizedText,
+    theme::PaletteColor,
+    widgets::{self, UiTheme},
+};
+
+/// The phase holding everything needed before the start screen can be shown.
+pub const BOOT_PHASE: &str = "boot";
+
+/// Assets that are still being loaded, grouped into named phases.
+///
+/// The boot phase is loaded behind the loading screen, other phases
+/// (such as per-screen or per-theme bundles) can be started on demand.
+#[derive(Default)]
+pub struct LoadingAssets {
+    phases: Vec<LoadPhase>,
+}
+
+struct LoadPhase {
+    name: String,
+    handles: Vec<HandleUntyped>,
+    errors: Vec<String>,
+}
+
+#[derive(Debug)]
+pub enum LoadingError {
+    PhaseAlreadyActive(String),
+    PhaseNotActive { phase: String, asset: String },
+}
+
+impl fmt::Display for LoadingError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            LoadingError::PhaseAlreadyActive(phase) => {
+                write!(f, "loading phase \"{}\" is already in progress", phase)
+            }
+            LoadingError::PhaseNotActive { phase, asset } => write!(
+                f,
+                "cannot register \"{}\": loading phase \"{}\" is not in progress",
+                asset, phase
+            ),
+        }
+    }
+}
+
+impl LoadingAssets {
+    pub fn begin_phase(&mut self, phase: &str) -> Result<(), LoadingError> {
+        if self.is_active(phase) {
+            return Err(LoadingError::PhaseAlreadyActive(phase.to_string()));
+        }
+
+        self.phases.push(LoadPhase {
+            name: phase.to_string(),
+            handles: vec![],
+            errors: vec![],
+        });
+        Ok(())
+    }
+
+    /// Adds a handle to a phase that is in progress. Registering into a phase that
+    /// has already completed (or was never started) is an error, as nothing would
+    /// wait for the asset to finish loading.
+    pub fn register(
+        &mut self,
+        phase: &str,
+        handle: HandleUntyped,
+        asset: &str,
+    ) -> Result<(), LoadingError> {
+        self.phase_mut(phase, asset)?.handles.push(handle);
+        Ok(())
+    }
+
+    /// Marks a phase as failed for a reason other than an asset failing to load.
+    pub fn fail(&mut self, phase: &str, reason: String) -> Result<(), LoadingError> {
+        self.phase_mut(phase, &reason)?.errors.push(reason);
+        Ok(())
+    }
+
+    pub fn is_active(&self, phase: &str) -> bool {
+        self.phases.iter().any(|active| active.name == phase)
+    }
+
+    fn phase_mut(&mut self, phase: &str, asset: &str) -> Result<&mut LoadPhase, LoadingError> {
+        self.phases
+            .iter_mut()
+            .find(|active| active.name == phase)
+            .ok_or_else(|| LoadingError::PhaseNotActive {
+                phase: phase.to_string(),
+                asset: asset.to_string(),
+            })
+    }
+}
 
 pub struct LoadingManagerPlugin;
 
 impl Plugin for LoadingManagerPlugin {
     fn build(&self, app: &mut App) {
-        app.insert_resource(LoadingAssets(vec![]))
+        app.insert_resource(LoadingAssets::default())
+            .insert_resource(LoadingProgress::default())
             .add_event::<FinishLoadingEvent>()
+            .add_event::<PhaseLoadedEvent>()
+            .add_event::<PhaseFailedEvent>()
             .add_startup_system(setup_loading)
-            .add_system(check_loading_system);
+            .add_system_set(
+                GameSystem::Input
+                    .set()
+                    .with_system(check_loading_system)
+                    .with_system(retry_loading_system),
+            )
+            .add_system_set(
+                GameSystem::Presentation
+                    .set()
+                    .with_system(loading_ui_system),
+            );
     }
 }
 
+const PROGRESS_BAR_WIDTH: f32 = 400.0;
+const PROGRESS_BAR_HEIGHT: f32 = 24.0;
+
+/// Sent once the boot phase has loaded.
 pub struct FinishLoadingEvent;
+/// Sent whenever any phase (including boot) has loaded.
+pub struct PhaseLoadedEvent(pub String);
+/// Sent when an on-demand phase cannot be loaded, and is given up on.
+pub struct PhaseFailedEvent(pub String);
+
+#[derive(Default)]
+struct LoadingProgress {
+    loaded: usize,
+    total: usize,
+    failed: Vec<String>,
+}
+
+#[derive(Component)]
+struct LoadingScreen;
 
 #[derive(Component)]
 struct LoadingText;
 
-fn setup_loading(
-    mut commands: Commands,
-    asset_server: Res<AssetServer>,
-    mut loading: ResMut<LoadingAssets>,
-) {
-    let font = asset_server.load("FiraSans-Bold.ttf");
-
-    commands
-        .spawn_bundle(TextBundle {
-            text: Text {
-                sections: vec![TextSection {
-                    value: "Loading...".to_string(),
-                    style: TextStyle {
-                        font: font.clone(),
-                        font_size: 60.0,
-                        color: Color::BLACK,
+#[derive(Component)]
+struct LoadingProgressBar;
+
+#[derive(Component)]
+struct LoadingErrorText;
+
+fn setup_loading(mut commands: Commands, ui_theme: Res<UiTheme>) {
+    widgets::screen(&mut commands)
+        .insert(LoadingScreen)
+        .with_children(|parent| {
+            widgets::title(parent, &ui_theme, "loading-progress").insert(LoadingText);
+
+            parent
+                .spawn_bundle(NodeBundle {
+                    style: Style {
+                        size: Size::new(Val::Px(PROGRESS_BAR_WIDTH), Val::Px(PROGRESS_BAR_HEIGHT)),
+                        margin: Rect::all(Val::Px(10.0)),
+                        ..Default::default()
                     },
-                }],
-                alignment: TextAlignment {
-                    vertical: VerticalAlign::Center,
-                    horizontal: HorizontalAlign::Center,
-                },
-            },
-            style: Style {
-                position_type: PositionType::Absolute,
-                position: Rect {
-                    top: Val::Percent(50.0),
-                    left: Val::Percent(50.0),
+                    color: UiColor(Color::rgb(0.2, 0.2, 0.2)),
                     ..Default::default()
-                },
-                ..Default::default()
-            },
-            ..Default::default()
-        })
-        .insert(LoadingText);
-
-    loading.0.push(font.clone_untyped());
+                })
+                .with_children(|parent| {
+                    parent
+                        .spawn_bundle(NodeBundle {
+                            style: Style {
+                                size: Size::new(Val::Percent(0.0), Val::Percent(100.0)),
+                                ..Default::default()
+                            },
+                            color: UiColor(Color::rgb(0.3, 0.8, 0.3)),
+                            ..Default::default()
+                        })
+                        .insert(LoadingProgressBar);
+                });
+
+            widgets::text(
+                parent,
+                &ui_theme,
+                "",
+                ui_theme.label_size,
+                PaletteColor::Highlight,
+            )
+            .insert(LoadingErrorText);
+        });
 }
 
 fn check_loading_system(
-    game_status: Res<GameState>,
-    mut query: Query<(&mut Text, &mut Visibility), With<LoadingText>>,
     asset_server: Res<AssetServer>,
-    mut commands: Commands,
-    loading: Option<Res<LoadingAssets>>,
+    mut progress: ResMut<LoadingProgress>,
+    mut loading: ResMut<LoadingAssets>,
     mut finish_loading_events: EventWriter<FinishLoadingEvent>,
+    mut phase_loaded_events: EventWriter<PhaseLoadedEvent>,
+    mut phase_failed_events: EventWriter<PhaseFailedEvent>,
 ) {
-    if crate::game_state::is_loading(&game_status) {
-        use bevy::asset::LoadState;
+    let loading = &mut *loading;
+    let mut finished = vec![];
 
-        match asset_server.get_group_load_state(loading.unwrap().0.iter().map(|h| h.id)) {
-            LoadState::Failed => {
-                query.iter_mut().for_each(|(mut text, _)| {
-                    text.sections[0].value = "Loading failed...".to_string();
-                });
-            }
-            LoadState::Loaded => {
-                finish_loading_events.send(FinishLoadingEvent);
+    loading.phases.iter().for_each(|phase| {
+        let mut loaded = 0;
+        let mut failed = phase.errors.clone();
 
-                query.iter_mut().for_each(|(_, mut visibility)| {
-                    visibility.is_visible = false;
-                });
+        phase
+            .handles
+            .iter()
+            .for_each(|handle| match asset_server.get_load_state(handle) {
+                LoadState::Loaded => loaded += 1,
+                LoadState::Failed => failed.push(asset_path_name(&asset_server, handle)),
+                _ => {}
+            });
 
-                commands.remove_resource::<LoadingAssets>();
+        if phase.name == BOOT_PHASE {
+            failed
+                .iter()
+                .filter(|path| !progress.failed.contains(path))
+                .for_each(|path| error!("Failed to load: {}", path));
+
+            progress.loaded = loaded;
+            progress.total = phase.handles.len();
+            progress.failed = failed.clone();
+        } else if !failed.is_empty() {
+            // on-demand phases have no screen of their own, so report them once and drop them
+            failed.iter().for_each(|path| {
+                error!(
+                    "Failed to load for loading phase \"{}\": {}",
+                    phase.name, path
+                )
+            });
+            finished.push((phase.name.clone(), false));
+        }
+
+        if failed.is_empty() && loaded == phase.handles.len() {
+            finished.push((phase.name.clone(), true));
+        }
+    });
+
+    finished.into_iter().for_each(|(name, loaded)| {
+        loading.phases.retain(|phase| phase.name != name);
+
+        if loaded {
+            if name == BOOT_PHASE {
+                finish_loading_events.send(FinishLoadingEvent);
             }
-            _ => {}
+
+            phase_loaded_events.send(PhaseLoadedEvent(name));
+        } else {
+            phase_failed_events.send(PhaseFailedEvent(name));
         }
+    });
+}
+
+fn loading_ui_system(
+    mut commands: Commands,
+    game_status: Res<GameState>,
+    progress: Res<LoadingProgress>,
+    screen_query: Query<Entity, With<LoadingScreen>>,
+    mut text_query: Query<&mut LocalizedText, (With<LoadingText>, Without<LoadingErrorText>)>,
+    mut error_query: Query<&mut LocalizedText, (With<LoadingErrorText>, Without<LoadingText>)>,
+    mut bar_query: Query<&mut Style, With<LoadingProgressBar>>,
+) {
+    if !crate::game_state::is_loading(&game_status) {
+        screen_query
+            .iter()
+            .for_each(|entity| commands.entity(entity).despawn_recursive());
+        return;
+    }
+
+    if !progress.is_changed() {
+        return;
+    }
+
+    text_query.iter_mut().for_each(|mut text| {
+        *text = if progress.failed.is_empty() {
+            LocalizedText::new("loading-progress")
+                .with_arg("loaded", progress.loaded)
+                .with_arg("total", progress.total)
+        } else {
+            LocalizedText::new("loading-failed")
+        };
+    });
+
+    error_query.iter_mut().for_each(|mut text| {
+        *text = if progress.failed.is_empty() {
+            LocalizedText::new("")
+        } else {
+            LocalizedText::new("loading-error").with_arg("assets", progress.failed.join(", "))
+        };
+    });
+
+    bar_query.iter_mut().for_each(|mut style| {
+        let fraction = if progress.total == 0 {
+            0.0
+        } else {
+            progress.loaded as f32 / progress.total as f32
+        };
+        style.size.width = Val::Percent(fraction * 100.0);
+    });
+}
+
+fn retry_loading_system(
+    game_status: Res<GameState>,
+    keyboard_input: Res<Input<KeyCode>>,
+    asset_server: Res<AssetServer>,
+    mut loading: ResMut<LoadingAssets>,
+) {
+    if crate::game_state::is_loading(&game_status) && keyboard_input.just_pressed(KeyCode::R) {
+        loading
+            .phases
+            .iter_mut()
+            .flat_map(|phase| phase.handles.iter_mut())
+            .for_each(|handle| {
+                if asset_server.get_load_state(&*handle) == LoadState::Failed {
+                    if let Some(path) = asset_server.get_handle_path(&*handle) {
+                        info!("Retrying asset: {}", path.path().display());
+                        *handle = asset_server.load_untyped(path);
+                    }
+                }
+            });
     }
 }
+
+fn asset_path_name(asset_server: &AssetServer, handle: &HandleUntyped) -> String {
+    asset_server
+        .get_handle_path(handle)
+        .map(|path| path.path().display().to_string())
+        .unwrap_or_else(|| format!("{:?}", handle.id))
+}
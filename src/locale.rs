@@ -0,0 +1,312 @@
+use std::collections::HashMap;
+
+use ab_glyph::Font as _;
+use bevy::{asset::AssetServerSettings, prelude::*, ui::UiSystem};
+
+use crate::{
+    game_assets::{GameAssets, GameAssetsSystem},
+    loading::{LoadingAssets, BOOT_PHASE},
+    profile::Profile,
+    widgets::UiTheme,
+};
+
+pub struct LocalePlugin;
+
+impl Plugin for LocalePlugin {
+    fn build(&self, app: &mut App) {
+        app.insert_resource(Locale::default())
+            .add_startup_system_to_stage(
+                StartupStage::PreStartup,
+                load_locales
+                    .label(LocaleSystem::Load)
+                    .after(GameAssetsSystem::Manifest),
+            )
+            // after the screens spawned their texts, but before they are laid out
+            .add_system_to_stage(
+                CoreStage::PostUpdate,
+                localized_text_system
+                    .label(LocaleSystem::Localize)
+                    .before(UiSystem::Flex),
+            )
+            .add_system_to_stage(
+                CoreStage::PostUpdate,
+                font_fallback_system
+                    .after(LocaleSystem::Localize)
+                    .before(UiSystem::Flex),
+            );
+    }
+}
+
+#[derive(SystemLabel, Debug, Clone, PartialEq, Eq, Hash)]
+pub enum LocaleSystem {
+    /// Startup system reading the string tables of every language
+    Load,
+    /// Fills in the text of every `LocalizedText`
+    Localize,
+}
+
+const LANGUAGE_NAME: &str = "language-name";
+
+/// The string tables of every language and which one is shown.
+#[derive(Default)]
+pub struct Locale {
+    languages: Vec<Language>,
+    active: usize,
+}
+
+struct Language {
+    id: String,
+    messages: HashMap<String, String>,
+}
+
+impl Locale {
+    pub fn active_id(&self) -> &str {
+        self.languages
+            .get(self.active)
+            .map_or("", |language| language.id.as_str())
+    }
+
+    /// The name of the active language, written in that language.
+    pub fn language_name(&self) -> String {
+        self.get(LANGUAGE_NAME)
+    }
+
+    /// Switches to the language after the active one, returning its id.
+    pub fn next_language(&mut self) -> &str {
+        if !self.languages.is_empty() {
+            self.active = (self.active + 1) % self.languages.len();
+        }
+        self.active_id()
+    }
+
+    pub fn contains(&self, key: &str) -> bool {
+        self.message(key).is_some()
+    }
+
+    pub fn get(&self, key: &str) -> String {
+        self.format(key, &[])
+    }
+
+    /// Looks up a message, falling back to the default language and then to the key itself,
+    /// and replaces its `{ $name }` placeholders with `args`. An empty key shows no text.
+    pub fn format(&self, key: &str, args: &[(String, String)]) -> String {
+        if key.is_empty() {
+            return String::new();
+        }
+
+        let message = match self.message(key) {
+            Some(message) => message,
+            None => {
+                warn!("No language has a string for \"{}\"", key);
+                return key.to_string();
+            }
+        };
+
+        let mut result = String::new();
+        let mut rest = message;
+
+        while let Some(start) = rest.find('{') {
+            result.push_str(&rest[..start]);
+
+            let end = match rest[start..].find('}') {
+                Some(end) => start + end,
+                None => break,
+            };
+
+            let name = rest[start + 1..end].trim().trim_start_matches('$');
+            match args.iter().find(|(arg, _)| arg == name) {
+                Some((_, value)) => result.push_str(value),
+                None => result.push_str(&rest[start..=end]),
+            }
+
+            rest = &rest[end + 1..];
+        }
+
+        result.push_str(rest);
+        result
+    }
+
+    fn message(&self, key: &str) -> Option<&str> {
+        [self.active, 0]
+            .iter()
+            .filter_map(|index| self.languages.get(*index))
+            .find_map(|language| language.messages.get(key))
+            .map(|message| message.as_str())
+    }
+}
+
+/// Parses a string table in a small subset of the Fluent syntax: `key = value` messages,
+/// values continued on indented lines, `{ $name }` placeholders and `#` comments.
+fn parse_messages(contents: &str) -> Result<HashMap<String, String>, String> {
+    let mut messages = HashMap::new();
+    let mut current: Option<(String, String)> = None;
+
+    for (number, line) in contents.lines().enumerate() {
+        if line.trim().is_empty() || line.trim_start().starts_with('#') {
+            continue;
+        }
+
+        if line.starts_with(char::is_whitespace) {
+            match current.as_mut() {
+                Some((_, value)) => {
+                    if !value.is_empty() {
+                        value.push('\n');
+                    }
+                    value.push_str(line.trim());
+                }
+                None => return Err(format!("line {}: continues no message", number + 1)),
+            }
+            continue;
+        }
+
+        let (key, value) = line
+            .split_once('=')
+            .ok_or_else(|| format!("line {}: expected \"key = value\"", number + 1))?;
+
+        if let Some((key, value)) = current.take() {
+            messages.insert(key, value);
+        }
+        current = Some((key.trim().to_string(), value.trim().to_string()));
+    }
+
+    if let Some((key, value)) = current {
+        messages.insert(key, value);
+    }
+
+    Ok(messages)
+}
+
+/// A text entity whose value comes from the string tables, kept up to date with the language.
+#[derive(Component, Clone)]
+pub struct LocalizedText {
+    pub key: String,
+    pub args: Vec<(String, String)>,
+}
+
+impl LocalizedText {
+    pub fn new(key: &str) -> Self {
+        Self {
+            key: key.to_string(),
+            args: vec![],
+        }
+    }
+
+    pub fn with_arg(mut self, name: &str, value: impl ToString) -> Self {
+        self.set_arg(name, value);
+        self
+    }
+
+    pub fn set_arg(&mut self, name: &str, value: impl ToString) {
+        let value = value.to_string();
+
+        match self.args.iter_mut().find(|(arg, _)| arg == name) {
+            Some((_, old)) => *old = value,
+            None => self.args.push((name.to_string(), value)),
+        }
+    }
+}
+
+impl From<&str> for LocalizedText {
+    fn from(key: &str) -> Self {
+        LocalizedText::new(key)
+    }
+}
+
+fn load_locales(
+    settings: Option<Res<AssetServerSettings>>,
+    game_assets: Res<GameAssets>,
+    profile: Res<Profile>,
+    mut locale: ResMut<Locale>,
+    mut loading: ResMut<LoadingAssets>,
+) {
+    game_assets.languages().iter().for_each(|id| {
+        let path = format!("locales/{}.ftl", id);
+
+        let result =
+            crate::game_assets::read_text_asset(settings.as_deref(), &path).and_then(|contents| {
+                parse_messages(&contents).map_err(|err| format!("{} {}", path, err))
+            });
+
+        match result {
+            Ok(messages) => locale.languages.push(Language {
+                id: id.clone(),
+                messages,
+            }),
+            Err(err) => {
+                let _ = loading.fail(BOOT_PHASE, format!("language \"{}\": {}", id, err));
+            }
+        }
+    });
+
+    locale.active = locale
+        .languages
+        .iter()
+        .position(|language| language.id == profile.language)
+        .unwrap_or(0);
+}
+
+fn localized_text_system(
+    locale: Res<Locale>,
+    mut query: Query<(ChangeTrackers<LocalizedText>, &LocalizedText, &mut Text)>,
+) {
+    query
+        .iter_mut()
+        .filter(|(tracker, _, _)| locale.is_changed() || tracker.is_changed())
+        .for_each(|(_, localized, mut text)| {
+            text.sections[0].value = locale.format(&localized.key, &localized.args);
+        });
+}
+
+/// Swaps in a fallback font for texts with characters the theme font has no glyphs for,
+/// such as scripts other than Latin.
+fn font_fallback_system(
+    fonts: Res<Assets<Font>>,
+    game_assets: Res<GameAssets>,
+    ui_theme: Res<UiTheme>,
+    mut query: Query<&mut Text, Changed<Text>>,
+) {
+    let candidates: Vec<Handle<Font>> = std::iter::once(ui_theme.font.clone())
+        .chain(
+            game_assets
+                .font_fallbacks()
+                .iter()
+                .map(|name| game_assets.font(name)),
+        )
+        .collect();
+
+    // fonts that are not loaded yet are assumed to cover everything
+    let covers = |font: &Handle<Font>, value: &str| {
+        fonts.get(font).map_or(true, |font| {
+            value
+                .chars()
+                .filter(|c| !c.is_whitespace())
+                .all(|c| font.font.glyph_id(c).0 != 0)
+        })
+    };
+
+    query.iter_mut().for_each(|mut text| {
+        let chosen: Vec<Option<Handle<Font>>> = text
+            .sections
+            .iter()
+            .map(|section| {
+                let font = candidates
+                    .iter()
+                    .find(|font| covers(font, &section.value))
+                    .unwrap_or(&ui_theme.font);
+                (section.style.font != *font).then(|| font.clone())
+            })
+            .collect();
+
+        // only touch the text when a font changes, otherwise it would count as changed forever
+        if chosen.iter().any(Option::is_some) {
+            text.sections
+                .iter_mut()
+                .zip(chosen)
+                .for_each(|(section, font)| {
+                    if let Some(font) = font {
+                        section.style.font = font;
+                    }
+                });
+        }
+    });
+}
@@ -1,44 +1,49 @@
-mod audio;
-mod background;
-mod game_core;
-mod game_state;
-mod ingame_ui;
-mod loading;
-mod mover;
-mod pillars;
-mod player;
-mod score;
-mod screen_end;
-mod screen_start;
-
-use audio::GameAudioPlugin;
-use background::BackgroundPlugin;
-use bevy::prelude::*;
-use game_core::GameCorePlugin;
-use game_state::GameStatePlugin;
-use ingame_ui::IngameUiPlugin;
-use loading::LoadingManagerPlugin;
-use mover::MoverPlugin;
-use pillars::PillarsPlugin;
-use player::PlayerPlugin;
-use score::ScorePlugin;
-use screen_end::ScreenEndPlugin;
-use screen_start::ScreenStartPlugin;
+use std::time::Duration;
+
+use bevy::{
+    app::{ScheduleRunnerPlugin, ScheduleRunnerSettings},
+    log::LogPlugin,
+    prelude::*,
+};
+use flappy_bird_bevy::{debug_schedule, FlappyGamePlugins, HeadlessPlugins};
+
+mod cli;
 
 fn main() {
-    App::new()
-        .add_plugins(DefaultPlugins)
-        .add_plugin(GameCorePlugin)
-        .add_plugin(ScorePlugin)
-        .add_plugin(GameStatePlugin)
-        .add_plugin(PlayerPlugin)
-        .add_plugin(GameAudioPlugin)
-        .add_plugin(LoadingManagerPlugin)
-        .add_plugin(IngameUiPlugin)
-        .add_plugin(MoverPlugin)
-        .add_plugin(PillarsPlugin)
-        .add_plugin(ScreenStartPlugin)
-        .add_plugin(ScreenEndPlugin)
-        .add_plugin(BackgroundPlugin)
-        .run();
+    let options = cli::Options::parse(std::env::args().skip(1)).unwrap_or_else(|err| {
+        eprintln!("error: {}\n\n{}", err, cli::USAGE);
+        std::process::exit(2);
+    });
+
+    if options.help {
+        print!("{}", cli::USAGE);
+        return;
+    }
+
+    let (headless, dump_schedule) = (options.headless, options.dump_schedule);
+    let mut app = App::new();
+
+    if let Err(err) = options.configure(&mut app) {
+        eprintln!("error: {}", err);
+        std::process::exit(2);
+    }
+
+    if headless {
+        // the clock follows the replay or its own fixed step, so frames can run back to back
+        app.insert_resource(ScheduleRunnerSettings::run_loop(Duration::ZERO))
+            .add_plugins(HeadlessPlugins)
+            .add_plugin(LogPlugin)
+            .add_plugin(ScheduleRunnerPlugin);
+    } else {
+        app.add_plugins(DefaultPlugins);
+    }
+
+    app.add_plugins(FlappyGamePlugins::default());
+
+    if dump_schedule {
+        print!("{}", debug_schedule::dump_schedule(&app.schedule));
+        return;
+    }
+
+    app.run();
 }
@@ -1,16 +1,29 @@
 use bevy::prelude::*;
 
-use crate::game_state::GameState;
+use crate::{
+    game_core::{GameClock, GameSystem, Playfield},
+    game_state::GameState,
+};
 
 pub struct MoverPlugin;
 
 impl Plugin for MoverPlugin {
     fn build(&self, app: &mut App) {
-        app.add_system(mover_system)
-            .add_system(mover_window_left_despawn_bound_system);
+        app.add_system_set(
+            GameSystem::Physics
+                .set()
+                .with_system(mover_system.label(MoverSystem::Move))
+                .with_system(mover_window_left_despawn_bound_system.after(MoverSystem::Move)),
+        );
     }
 }
 
+#[derive(SystemLabel, Debug, Clone, PartialEq, Eq, Hash)]
+pub enum MoverSystem {
+    /// Integrates the velocity of every active mover
+    Move,
+}
+
 #[derive(Component)]
 pub struct Mover {
     pub active: bool,
@@ -25,7 +38,7 @@ pub struct MoverWindowLeftDespawnBound {
 
 fn mover_system(
     game_state: Res<GameState>,
-    time: Res<Time>,
+    time: Res<GameClock>,
     mut query: Query<(&mut Mover, &mut Transform)>,
 ) {
     if crate::game_state::is_playing(&game_state) {
@@ -41,11 +54,10 @@ fn mover_system(
 
 fn mover_window_left_despawn_bound_system(
     game_state: Res<GameState>,
-    windows: Res<Windows>,
+    playfield: Res<Playfield>,
     mut query: Query<(&MoverWindowLeftDespawnBound, &mut Mover, &mut Transform)>,
 ) {
-    let window = windows.get_primary().unwrap();
-    let window_width = window.width() as f32;
+    let playfield_width = playfield.width;
 
     if crate::game_state::is_playing(&game_state) {
         query
@@ -53,13 +65,13 @@ fn mover_window_left_despawn_bound_system(
             .for_each(|(mover_window_bound, mut mover, mut transform)| {
                 if mover.active
                     && transform.translation.x
-                        < (-window_width / 2.0) - (mover_window_bound.object_width / 2.0)
+                        < (-playfield_width / 2.0) - (mover_window_bound.object_width / 2.0)
                 {
                     mover.active = false;
 
                     // hack to avoid dealing with visibility
                     // (have to modify children which is troublesome...)
-                    transform.translation.x = window_width;
+                    transform.translation.x = playfield_width;
                 }
             });
     }
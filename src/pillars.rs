@@ -1,59 +1,158 @@
 use bevy::prelude::*;
+use rand::Rng;
+use serde::{Deserialize, Serialize};
 
 use crate::{
-    game_state::{GameState, StartNewGameEvent},
-    loading::LoadingAssets,
+    game_assets::GameAssets,
+    game_core::{GameClock, GameRng, GameSystem, Playfield, Tuning},
+    game_state::{GameState, GameStateSystem, ResetGameEvent, StartNewGameEvent},
     mover::{Mover, MoverWindowLeftDespawnBound},
-    player::{Player, PlayerCrossedPillarEvent, PlayerKilledEvent},
+    player::{
+        CrossCandidateEvent, DeathCause, KillCandidateEvent, Player, PlayerKilledEvent, RunTime,
+    },
+    theme::ThemedSprite,
 };
 
 pub struct PillarsPlugin;
 
 impl Plugin for PillarsPlugin {
     fn build(&self, app: &mut App) {
+        let difficulty = *app.world.get_resource_or_insert_with(Difficulty::default);
+        let tuning = *app.world.get_resource_or_insert_with(Tuning::default);
+
         app.insert_resource(PillarSpawnerTimer(Timer::from_seconds(
-            NEXT_PILLAR_SPAWN_TIME,
+            difficulty.settings(&tuning).spawn_time,
             true,
         )))
         .insert_resource(PillarPool(vec![]))
         .add_startup_system(setup_pillars)
-        .add_system(new_game_system)
-        .add_system(player_pillar_check_system)
-        .add_system(pillar_spawn_system);
+        .add_system_set(
+            GameSystem::State
+                .set()
+                .with_system(new_game_system.after(GameStateSystem::Transition)),
+        )
+        .add_system_set(GameSystem::Physics.set().with_system(pillar_spawn_system))
+        .add_system_set(
+            GameSystem::Collision
+                .set()
+                .with_system(player_pillar_check_system),
+        );
     }
 }
 
-const PILLAR_GAP: f32 = 150.0;
 const PILLAR_HEIGHT: f32 = 1024.0;
-const PILLAR_WIDTH: f32 = 128.0;
+pub const PILLAR_WIDTH: f32 = 128.0;
 const PLAYER_VISIBLE_HEIGHT: f32 = 46.0;
 
-const NEXT_PILLAR_SPAWN_TIME: f32 = 3.0;
+/// How hard the pillars are to get through. Inserted before the plugins, as it is only
+/// read when they are set up.
+#[derive(Serialize, Deserialize, Clone, Copy, PartialEq, Debug)]
+pub enum Difficulty {
+    Easy,
+    Normal,
+    Hard,
+}
+
+impl Default for Difficulty {
+    fn default() -> Self {
+        Difficulty::Normal
+    }
+}
+
+struct DifficultySettings {
+    /// height of the opening between the top and bottom pillar
+    gap: f32,
+    speed: f32,
+    /// seconds between two pillars
+    spawn_time: f32,
+}
+
+impl std::str::FromStr for Difficulty {
+    type Err = String;
+
+    fn from_str(value: &str) -> Result<Self, Self::Err> {
+        match value {
+            "easy" => Ok(Difficulty::Easy),
+            "normal" => Ok(Difficulty::Normal),
+            "hard" => Ok(Difficulty::Hard),
+            _ => Err(format!(
+                "invalid difficulty {}, expected easy, normal or hard",
+                value
+            )),
+        }
+    }
+}
+
+impl Difficulty {
+    /// Height of the opening between the top and bottom pillar.
+    pub fn pillar_gap(&self, tuning: &Tuning) -> f32 {
+        self.settings(tuning).gap
+    }
 
-const PILLAR_SPEED: f32 = 150.0;
+    fn settings(&self, tuning: &Tuning) -> DifficultySettings {
+        let settings = match self {
+            Difficulty::Easy => DifficultySettings {
+                gap: 190.0,
+                speed: 130.0,
+                spawn_time: 3.4,
+            },
+            Difficulty::Normal => DifficultySettings {
+                gap: 150.0,
+                speed: 150.0,
+                spawn_time: 3.0,
+            },
+            Difficulty::Hard => DifficultySettings {
+                gap: 125.0,
+                speed: 175.0,
+                spawn_time: 2.6,
+            },
+        };
+
+        DifficultySettings {
+            gap: tuning.pillar_gap.unwrap_or(settings.gap),
+            speed: tuning.pillar_speed.unwrap_or(settings.speed),
+            ..settings
+        }
+    }
+}
 
 struct PillarPool(Vec<Entity>);
 struct PillarSpawnerTimer(Timer);
 
 #[derive(Component)]
-struct Pillar {
+pub struct Pillar {
     player_crossed: bool,
 }
 
+/// The part of the pillar centred on `pillar` that a player at height `player_y` hits, if any.
+pub fn pillar_hit(player_y: f32, pillar: Vec3, gap: f32) -> Option<DeathCause> {
+    if pillar.x > PILLAR_WIDTH / 2.0 || pillar.x < -(PILLAR_WIDTH / 2.0) {
+        return None;
+    }
+
+    let top = gap / 2.0 + pillar.y;
+    let bottom = -gap / 2.0 + pillar.y;
+
+    if player_y > top - (PLAYER_VISIBLE_HEIGHT / 2.0) {
+        Some(DeathCause::TopPillar)
+    } else if player_y < bottom + (PLAYER_VISIBLE_HEIGHT / 2.0) {
+        Some(DeathCause::BottomPillar)
+    } else {
+        None
+    }
+}
+
 fn setup_pillars(
     mut commands: Commands,
-    asset_server: Res<AssetServer>,
-    mut windows: ResMut<Windows>,
-    mut loading: ResMut<LoadingAssets>,
+    game_assets: Res<GameAssets>,
+    playfield: Res<Playfield>,
+    difficulty: Res<Difficulty>,
+    tuning: Res<Tuning>,
     mut pillar_pools: ResMut<PillarPool>,
 ) {
-    let window = windows.get_primary_mut().unwrap();
-
-    let pillar_top = asset_server.load("pillar_top.png");
-    let pillar_bottom = asset_server.load("pillar_bottom.png");
-
-    loading.0.push(pillar_top.clone_untyped());
-    loading.0.push(pillar_bottom.clone_untyped());
+    let settings = difficulty.settings(&tuning);
+    let pillar_top = game_assets.texture("pillar_top");
+    let pillar_bottom = game_assets.texture("pillar_bottom");
 
     pillar_pools.0.extend((0..10).map(|_| {
         commands
@@ -62,7 +161,7 @@ fn setup_pillars(
                 player_crossed: false,
             })
             .insert(Transform {
-                translation: Vec3::new(window.width(), 0.0, 0.0),
+                translation: Vec3::new(playfield.width, 0.0, 0.0),
                 ..Default::default()
             })
             .insert(GlobalTransform {
@@ -70,53 +169,55 @@ fn setup_pillars(
             })
             .insert(Mover {
                 active: false,
-                velocity: Vec3::new(-PILLAR_SPEED, 0.0, 0.0),
+                velocity: Vec3::new(-settings.speed, 0.0, 0.0),
                 acceleration: Vec3::ZERO,
             })
             .insert(MoverWindowLeftDespawnBound {
                 object_width: PILLAR_WIDTH,
             })
             .with_children(|parent| {
-                parent.spawn_bundle(SpriteBundle {
-                    texture: pillar_top.clone(),
-                    transform: Transform {
-                        translation: Vec3::new(
-                            0.0,
-                            (PILLAR_HEIGHT / 2.0) + (PILLAR_GAP / 2.0),
-                            0.0,
-                        ),
+                parent
+                    .spawn_bundle(SpriteBundle {
+                        texture: pillar_top.clone(),
+                        transform: Transform {
+                            translation: Vec3::new(
+                                0.0,
+                                (PILLAR_HEIGHT / 2.0) + (settings.gap / 2.0),
+                                0.0,
+                            ),
+                            ..Default::default()
+                        },
                         ..Default::default()
-                    },
-                    ..Default::default()
-                });
-
-                parent.spawn_bundle(SpriteBundle {
-                    texture: pillar_bottom.clone(),
-                    transform: Transform {
-                        translation: Vec3::new(
-                            0.0,
-                            -(PILLAR_HEIGHT / 2.0) - (PILLAR_GAP / 2.0),
-                            0.0,
-                        ),
+                    })
+                    .insert(ThemedSprite("pillar_top"));
+
+                parent
+                    .spawn_bundle(SpriteBundle {
+                        texture: pillar_bottom.clone(),
+                        transform: Transform {
+                            translation: Vec3::new(
+                                0.0,
+                                -(PILLAR_HEIGHT / 2.0) - (settings.gap / 2.0),
+                                0.0,
+                            ),
+                            ..Default::default()
+                        },
                         ..Default::default()
-                    },
-                    ..Default::default()
-                });
+                    })
+                    .insert(ThemedSprite("pillar_bottom"));
             })
             .id()
     }));
 }
 
 fn new_game_system(
-    windows: Res<Windows>,
+    playfield: Res<Playfield>,
     mut start_new_events: EventReader<StartNewGameEvent>,
+    mut reset_events: EventReader<ResetGameEvent>,
     mut query: Query<(&mut Mover, &mut Pillar, &mut Transform)>,
     mut timer: ResMut<PillarSpawnerTimer>,
 ) {
-    if start_new_events.iter().count() > 0 {
-        let window = windows.get_primary().unwrap();
-        let window_width = window.width();
-
+    if start_new_events.iter().count() + reset_events.iter().count() > 0 {
         query
             .iter_mut()
             .for_each(|(mut mover, mut pillar, mut transform)| {
@@ -125,7 +226,7 @@ fn new_game_system(
 
                 // hack to avoid dealing with visibility
                 // (have to modify children which is troublesome...)
-                transform.translation.x = window_width;
+                transform.translation.x = playfield.width;
             });
 
         timer.0.reset();
@@ -134,60 +235,68 @@ fn new_game_system(
 
 fn player_pillar_check_system(
     game_state: Res<GameState>,
-    mut query: Query<(&Transform, &mut Pillar, &Mover), Without<Player>>,
+    run_time: Res<RunTime>,
+    difficulty: Res<Difficulty>,
+    tuning: Res<Tuning>,
+    mut query: Query<(Entity, &Transform, &mut Pillar, &Mover), Without<Player>>,
     player_query: Query<&Transform, With<Player>>,
-    mut cross_event: EventWriter<PlayerCrossedPillarEvent>,
-    mut killed_event: EventWriter<PlayerKilledEvent>,
+    mut cross_candidates: EventWriter<CrossCandidateEvent>,
+    mut kill_candidates: EventWriter<KillCandidateEvent>,
 ) {
     let player_transform = player_query.single();
+    let gap = difficulty.pillar_gap(&tuning);
 
     if crate::game_state::is_playing(&game_state) {
-        query.iter_mut().for_each(|(transform, mut pillar, mover)| {
-            if mover.active
-                && transform.translation.x <= (PILLAR_WIDTH / 2.0)
-                && transform.translation.x >= -(PILLAR_WIDTH / 2.0)
-            {
-                let top = PILLAR_GAP / 2.0 + transform.translation.y;
-                let bottom = -PILLAR_GAP / 2.0 + transform.translation.y;
-
-                if player_transform.translation.y > top - (PLAYER_VISIBLE_HEIGHT / 2.0)
-                    || player_transform.translation.y < bottom + (PLAYER_VISIBLE_HEIGHT / 2.0)
-                {
-                    killed_event.send(PlayerKilledEvent);
-                // divide by 4.0 => to allow player to score when he reaches 75% across the pillar
-                } else if transform.translation.x < -(PILLAR_WIDTH / 4.0) && !pillar.player_crossed
+        query
+            .iter_mut()
+            .for_each(|(entity, transform, mut pillar, mover)| {
+                if mover.active
+                    && transform.translation.x <= (PILLAR_WIDTH / 2.0)
+                    && transform.translation.x >= -(PILLAR_WIDTH / 2.0)
                 {
-                    pillar.player_crossed = true;
-                    cross_event.send(PlayerCrossedPillarEvent);
+                    let cause =
+                        pillar_hit(player_transform.translation.y, transform.translation, gap);
+
+                    if let Some(cause) = cause {
+                        kill_candidates.send(KillCandidateEvent(PlayerKilledEvent {
+                            cause,
+                            pillar: Some(entity),
+                            position: player_transform.translation,
+                            run_time: run_time.0,
+                        }));
+                    // divide by 4.0 => to allow player to score when he reaches 75% across the pillar
+                    } else if transform.translation.x < -(PILLAR_WIDTH / 4.0)
+                        && !pillar.player_crossed
+                    {
+                        pillar.player_crossed = true;
+                        cross_candidates.send(CrossCandidateEvent);
+                    }
                 }
-            }
-        });
+            });
     }
 }
 
 fn pillar_spawn_system(
-    windows: Res<Windows>,
-    time: Res<Time>,
+    playfield: Res<Playfield>,
+    time: Res<GameClock>,
     game_state: Res<GameState>,
+    mut rng: ResMut<GameRng>,
     mut timer: ResMut<PillarSpawnerTimer>,
     pillar_pools: Res<PillarPool>,
     mut pillar_query: Query<(&mut Pillar, &mut Transform, &mut Mover)>,
 ) {
     if crate::game_state::is_playing(&game_state) && timer.0.tick(time.delta()).just_finished() {
-        let window = windows.get_primary().unwrap();
-        let window_width = window.width() as f32;
-        let window_height = window.height() as f32;
-
         let mut found = false;
 
         for child in pillar_pools.0.iter() {
             let (mut pillar, mut transform, mut mover) = pillar_query.get_mut(*child).unwrap();
             if !mover.active {
-                let gap_y = ((rand::random::<f32>() - 0.5) * 2.0) * ((window_height - 100.0) / 2.0);
+                let gap_y =
+                    ((rng.run().gen::<f32>() - 0.5) * 2.0) * ((playfield.height - 100.0) / 2.0);
 
                 mover.active = true;
                 pillar.player_crossed = false;
-                transform.translation.x = (window_width / 2.0) + (PILLAR_WIDTH / 2.0);
+                transform.translation.x = (playfield.width / 2.0) + (PILLAR_WIDTH / 2.0);
                 transform.translation.y = gap_y;
 
                 found = true;
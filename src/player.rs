@@ -1,93 +1,279 @@
 use bevy::prelude::*;
 
 use crate::{
-    game_state::{GameState, StartNewGameEvent},
-    loading::LoadingAssets,
-    mover::Mover,
+    audio::{AudioCue, PlayAudioCueEvent},
+    game_assets::GameAssets,
+    game_core::{GameClock, GameSystem, Playfield, ResolveSystem, Tuning},
+    game_state::{BeginPlayEvent, GameState, GameStateSystem, ResetGameEvent, StartNewGameEvent},
+    mover::{Mover, MoverSystem},
+    theme::ThemedSprite,
 };
 
 pub struct PlayerPlugin;
 
 impl Plugin for PlayerPlugin {
     fn build(&self, app: &mut App) {
-        app.add_event::<PlayerCrossedPillarEvent>()
+        app.init_resource::<Tuning>()
+            .insert_resource(RunTime(0.0))
+            .init_resource::<PlayerControl>()
+            .add_event::<PlayerCrossedPillarEvent>()
             .add_event::<PlayerKilledEvent>()
+            .add_event::<PlayerFlappedEvent>()
+            .add_event::<CrossCandidateEvent>()
+            .add_event::<KillCandidateEvent>()
             .add_startup_system(setup_player)
-            .add_system(new_game_system)
-            .add_system(player_input_system)
-            .add_system(player_bounds_check_system);
+            .add_system_set(GameSystem::Input.set().with_system(player_input_system))
+            .add_system_set(
+                GameSystem::State
+                    .set()
+                    .with_system(new_game_system.after(GameStateSystem::Transition))
+                    .with_system(begin_play_system),
+            )
+            .add_system_set(
+                GameSystem::Physics
+                    .set()
+                    .with_system(run_time_system)
+                    .with_system(player_hover_system)
+                    .with_system(flap_system.before(MoverSystem::Move)),
+            )
+            .add_system_set(
+                GameSystem::Collision
+                    .set()
+                    .with_system(player_bounds_check_system),
+            )
+            .add_system_set(
+                GameSystem::Scoring
+                    .set()
+                    .with_system(resolve_run_system.label(ResolveSystem::Run)),
+            );
     }
 }
 
-const PLAYER_GRAVITY: f32 = 9.81 * 60.0;
-const LEAP_Y_VELOCITY: f32 = 5.0 * 60.0;
+/// Downward acceleration of the player, unless tuned.
+pub const PLAYER_GRAVITY: f32 = 9.81 * 60.0;
+/// Upward speed of the player right after a flap.
+pub const LEAP_Y_VELOCITY: f32 = 5.0 * 60.0;
+const HOVER_HEIGHT: f32 = 12.0;
+const HOVER_SPEED: f64 = 3.0;
 
+#[derive(Clone)]
 pub struct PlayerCrossedPillarEvent;
-pub struct PlayerKilledEvent;
+#[derive(Clone)]
+pub struct PlayerKilledEvent {
+    pub cause: DeathCause,
+    /// the pillar that was hit, if any
+    pub pillar: Option<Entity>,
+    pub position: Vec3,
+    /// seconds since the run began
+    pub run_time: f32,
+}
+pub struct PlayerFlappedEvent;
+
+/// The player passed a pillar during this frame, scored once the frame is resolved.
+pub struct CrossCandidateEvent;
+/// The player hit something during this frame. All candidates of a frame are resolved
+/// into a single `PlayerKilledEvent`.
+pub struct KillCandidateEvent(pub PlayerKilledEvent);
+
+#[derive(Clone, Copy, Debug)]
+pub enum DeathCause {
+    TopPillar,
+    BottomPillar,
+    Ceiling,
+    Floor,
+}
 
 #[derive(Component)]
 pub struct Player;
 
-fn setup_player(
-    mut commands: Commands,
-    asset_server: Res<AssetServer>,
-    mut loading: ResMut<LoadingAssets>,
-) {
-    let player = asset_server.load("player.png");
-    loading.0.push(player.clone_untyped());
+/// Seconds spent playing in the current run, not counting the get ready countdown.
+pub struct RunTime(pub f32);
+
+/// Where the taps that flap the bird come from. While scripted, the keyboard, mouse and gamepad
+/// are ignored from the countdown to the game over screen, and `tap` stands in for them.
+/// Scripted runs are left out of the profile and the statistics.
+#[derive(Default)]
+pub struct PlayerControl {
+    pub scripted: bool,
+    /// whether the script taps during this frame, set before the `GameSystem::Input` phase
+    pub tap: bool,
+}
+
+/// Downward acceleration of the player with the `Tuning` of the game.
+pub fn player_gravity(tuning: &Tuning) -> f32 {
+    tuning.player_gravity.unwrap_or(PLAYER_GRAVITY)
+}
 
+fn setup_player(mut commands: Commands, game_assets: Res<GameAssets>, tuning: Res<Tuning>) {
     commands
         .spawn_bundle(SpriteBundle {
-            texture: player,
+            texture: game_assets.texture("player"),
             ..Default::default()
         })
         .insert(Player)
+        .insert(ThemedSprite("player"))
         .insert(Mover {
             active: true,
             velocity: Vec3::ZERO,
-            acceleration: Vec3::new(0.0, -PLAYER_GRAVITY, 0.0),
+            acceleration: Vec3::new(0.0, -player_gravity(&tuning), 0.0),
         });
 }
 
 fn player_input_system(
     game_status: Res<GameState>,
+    control: Res<PlayerControl>,
     keyboard_input: Res<Input<KeyCode>>,
+    mut flapped_events: EventWriter<PlayerFlappedEvent>,
+) {
+    let tapped = if control.scripted {
+        control.tap
+    } else {
+        keyboard_input.just_pressed(KeyCode::Space)
+    };
+
+    if crate::game_state::is_playing(&game_status) && tapped {
+        flapped_events.send(PlayerFlappedEvent);
+    }
+}
+
+fn begin_play_system(
+    mut begin_play_events: EventReader<BeginPlayEvent>,
+    mut flapped_events: EventWriter<PlayerFlappedEvent>,
+) {
+    if begin_play_events.iter().any(|event| event.flap) {
+        flapped_events.send(PlayerFlappedEvent);
+    }
+}
+
+fn flap_system(
     mut query: Query<&mut Mover, With<Player>>,
+    mut flapped_events: EventReader<PlayerFlappedEvent>,
+    mut audio_cue_events: EventWriter<PlayAudioCueEvent>,
 ) {
-    let mut mover = query.single_mut();
+    if flapped_events.iter().count() > 0 {
+        let mut mover = query.single_mut();
 
-    if crate::game_state::is_playing(&game_status) && keyboard_input.just_pressed(KeyCode::Space) {
         mover.velocity.y = LEAP_Y_VELOCITY;
+        audio_cue_events.send(PlayAudioCueEvent(AudioCue::Flap));
+    }
+}
+
+/// Bobs the bird up and down in place while the menus or the get ready countdown are shown.
+/// The bobbing restarts with every run, so where the bird is when play begins only depends on
+/// the run itself.
+fn player_hover_system(
+    mut hover_time: Local<f64>,
+    time: Res<GameClock>,
+    game_status: Res<GameState>,
+    mut new_game_events: EventReader<StartNewGameEvent>,
+    mut reset_events: EventReader<ResetGameEvent>,
+    mut query: Query<&mut Transform, With<Player>>,
+) {
+    if new_game_events.iter().count() + reset_events.iter().count() > 0 {
+        *hover_time = 0.0;
+    }
+
+    if game_status.0.is_menu() || crate::game_state::is_get_ready(&game_status) {
+        let mut transform = query.single_mut();
+
+        *hover_time += time.delta().as_secs_f64();
+        transform.translation.y = hover_y(*hover_time);
+    }
+}
+
+/// Height of the bobbing bird `hover_time` seconds after it started bobbing.
+pub fn hover_y(hover_time: f64) -> f32 {
+    HOVER_HEIGHT * (hover_time * HOVER_SPEED).sin() as f32
+}
+
+fn run_time_system(
+    time: Res<GameClock>,
+    game_status: Res<GameState>,
+    mut run_time: ResMut<RunTime>,
+) {
+    if crate::game_state::is_playing(&game_status) {
+        run_time.0 += time.delta_seconds();
     }
 }
 
 fn player_bounds_check_system(
-    windows: Res<Windows>,
+    playfield: Res<Playfield>,
     game_status: Res<GameState>,
+    run_time: Res<RunTime>,
     mut query: Query<&Transform, With<Player>>,
-    mut killed_event: EventWriter<PlayerKilledEvent>,
+    mut kill_candidates: EventWriter<KillCandidateEvent>,
 ) {
     let transform = query.single_mut();
 
     if crate::game_state::is_playing(&game_status) {
-        let window = windows.get_primary().unwrap();
+        let (min_y, max_y) = (-playfield.height / 2.0, playfield.height / 2.0);
 
-        let (min_y, max_y) = (-window.height() as f32 / 2.0, window.height() as f32 / 2.0);
+        let cause = if transform.translation.y < min_y {
+            Some(DeathCause::Floor)
+        } else if transform.translation.y > max_y {
+            Some(DeathCause::Ceiling)
+        } else {
+            None
+        };
 
-        if transform.translation.y < min_y || transform.translation.y > max_y {
-            killed_event.send(PlayerKilledEvent);
+        if let Some(cause) = cause {
+            kill_candidates.send(KillCandidateEvent(PlayerKilledEvent {
+                cause,
+                pillar: None,
+                position: transform.translation,
+                run_time: run_time.0,
+            }));
         }
     }
 }
 
 fn new_game_system(
+    mut run_time: ResMut<RunTime>,
     mut query: Query<(&mut Transform, &mut Mover), With<Player>>,
     mut new_game_events: EventReader<StartNewGameEvent>,
+    mut reset_events: EventReader<ResetGameEvent>,
 ) {
-    if new_game_events.iter().count() > 0 {
+    if new_game_events.iter().count() + reset_events.iter().count() > 0 {
         let (mut transform, mut mover) = query.single_mut();
 
         transform.translation = Vec3::ZERO;
         mover.velocity = Vec3::ZERO;
+        run_time.0 = 0.0;
+    }
+}
+
+/// Settles the frame: crossings count as long as the run is on, including the ones of the
+/// frame the player died in, and of all kills the pillar hits win over leaving the playfield.
+fn resolve_run_system(
+    mut run_over: Local<bool>,
+    mut new_game_events: EventReader<StartNewGameEvent>,
+    mut reset_events: EventReader<ResetGameEvent>,
+    mut cross_candidates: EventReader<CrossCandidateEvent>,
+    mut kill_candidates: EventReader<KillCandidateEvent>,
+    mut crossed_events: EventWriter<PlayerCrossedPillarEvent>,
+    mut killed_events: EventWriter<PlayerKilledEvent>,
+) {
+    if new_game_events.iter().count() + reset_events.iter().count() > 0 {
+        *run_over = false;
+    }
+
+    let crossed = cross_candidates.iter().count();
+    let killed = kill_candidates.iter().min_by_key(|candidate| {
+        let hit_pillar = matches!(
+            candidate.0.cause,
+            DeathCause::TopPillar | DeathCause::BottomPillar
+        );
+        (!hit_pillar, candidate.0.pillar)
+    });
+
+    if *run_over {
+        return;
+    }
+
+    (0..crossed).for_each(|_| crossed_events.send(PlayerCrossedPillarEvent));
+
+    if let Some(candidate) = killed {
+        *run_over = true;
+        killed_events.send(candidate.0.clone());
     }
 }
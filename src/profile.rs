@@ -0,0 +1,173 @@
+use std::path::PathBuf;
+
+use bevy::{asset::FileAssetIo, prelude::*};
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    game_core::{GameSystem, ScalingMode},
+    game_state::{GameStateType, OnGameStateChangedEvent},
+    player::PlayerControl,
+    score::Score,
+};
+
+pub struct ProfilePlugin;
+
+impl Plugin for ProfilePlugin {
+    fn build(&self, app: &mut App) {
+        let settings = app
+            .world
+            .get_resource_or_insert_with(SaveSettings::default)
+            .clone();
+
+        // loaded right away, as other plugins read it during startup
+        app.insert_resource(Profile::load(&settings))
+            .add_system_set(
+                GameSystem::Presentation
+                    .set()
+                    .with_system(best_score_system.label(ProfileSystem::BestScore))
+                    .with_system(high_scores_system)
+                    .with_system(save_profile_system),
+            );
+    }
+}
+
+#[derive(SystemLabel, Debug, Clone, PartialEq, Eq, Hash)]
+pub enum ProfileSystem {
+    /// Raises the best score once a run is over
+    BestScore,
+}
+
+const PROFILE_FILE: &str = "profile.ron";
+const SAVE_FOLDER: &str = "save";
+const HIGH_SCORE_COUNT: usize = 10;
+
+/// Everything remembered about the player between sessions.
+#[derive(Serialize, Deserialize)]
+#[serde(default)]
+pub struct Profile {
+    pub best_score: u32,
+    /// best scores of all time, highest first
+    pub high_scores: Vec<u32>,
+    pub theme: String,
+    pub scaling: ScalingMode,
+    pub language: String,
+}
+
+impl Default for Profile {
+    fn default() -> Self {
+        Self {
+            best_score: 0,
+            high_scores: vec![],
+            theme: "day".to_string(),
+            scaling: ScalingMode::Letterbox,
+            language: "en".to_string(),
+        }
+    }
+}
+
+/// Where the profile and statistics are kept. Inserted before the plugins, it may point
+/// elsewhere or turn saving off, so that a headless run leaves the player's saves alone.
+#[derive(Clone)]
+pub struct SaveSettings {
+    /// `None` reads and writes nothing, every session starting afresh
+    pub folder: Option<PathBuf>,
+}
+
+impl Default for SaveSettings {
+    fn default() -> Self {
+        // the save folder sits next to the assets folder
+        Self {
+            folder: Some(FileAssetIo::get_root_path().join(SAVE_FOLDER)),
+        }
+    }
+}
+
+impl Profile {
+    fn load(settings: &SaveSettings) -> Self {
+        let path = match save_path(settings, PROFILE_FILE) {
+            Some(path) => path,
+            None => return Profile::default(),
+        };
+
+        match std::fs::read_to_string(&path) {
+            Ok(contents) => ron::from_str(&contents).unwrap_or_else(|err| {
+                error!("Ignoring unreadable profile {}: {}", path.display(), err);
+                Profile::default()
+            }),
+            Err(_) => Profile::default(),
+        }
+    }
+
+    fn save(&self, settings: &SaveSettings) {
+        let path = match save_path(settings, PROFILE_FILE) {
+            Some(path) => path,
+            None => return,
+        };
+
+        let result = ron::ser::to_string_pretty(self, Default::default())
+            .map_err(|err| err.to_string())
+            .and_then(|contents| write_save_file(&path, &contents));
+
+        if let Err(err) = result {
+            error!("Cannot save profile {}: {}", path.display(), err);
+        }
+    }
+}
+
+/// Location of a file in the save folder, if saving is on.
+pub fn save_path(settings: &SaveSettings, file_name: &str) -> Option<PathBuf> {
+    settings
+        .folder
+        .as_ref()
+        .map(|folder| folder.join(file_name))
+}
+
+pub fn write_save_file(path: &std::path::Path, contents: &str) -> Result<(), String> {
+    if let Some(folder) = path.parent() {
+        std::fs::create_dir_all(folder).map_err(|err| err.to_string())?;
+    }
+    std::fs::write(path, contents).map_err(|err| err.to_string())
+}
+
+/// Raises the best score when a run ends rather than on every point, as the profile is saved
+/// whenever it changes.
+fn best_score_system(
+    score: Res<Score>,
+    control: Res<PlayerControl>,
+    mut profile: ResMut<Profile>,
+    mut game_state_changed_events: EventReader<OnGameStateChangedEvent>,
+) {
+    game_state_changed_events.iter().for_each(|event| {
+        if matches!(event.0, GameStateType::GameOver)
+            && !control.scripted
+            && score.0 > profile.best_score
+        {
+            profile.best_score = score.0;
+        }
+    });
+}
+
+fn high_scores_system(
+    score: Res<Score>,
+    control: Res<PlayerControl>,
+    mut profile: ResMut<Profile>,
+    mut game_state_changed_events: EventReader<OnGameStateChangedEvent>,
+) {
+    game_state_changed_events.iter().for_each(|event| {
+        if matches!(event.0, GameStateType::GameOver) && score.0 > 0 && !control.scripted {
+            let high_scores = &mut profile.high_scores;
+            let position = high_scores.partition_point(|high_score| *high_score >= score.0);
+
+            if position < HIGH_SCORE_COUNT {
+                high_scores.insert(position, score.0);
+                high_scores.truncate(HIGH_SCORE_COUNT);
+            }
+        }
+    });
+}
+
+fn save_profile_system(settings: Res<SaveSettings>, profile: Res<Profile>) {
+    if profile.is_changed() && !profile.is_added() {
+        profile.save(&settings);
+    }
+}
@@ -0,0 +1,269 @@
+use std::{path::PathBuf, time::Duration};
+
+use bevy::{app::AppExit, core::CoreSystem, prelude::*};
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    game_core::{GameClock, GameRng, GameSystem},
+    game_state::{GameState, GameStateType, OnGameStateChangedEvent, StartNewGameEvent},
+    pillars::Difficulty,
+    player::{PlayerControl, PlayerFlappedEvent},
+    profile::write_save_file,
+    score::Score,
+};
+
+/// Records runs to a file, or plays a recorded run back, as set by the `ReplayMode`. The mode
+/// may be inserted before the plugins or changed at any time. Whatever the mode, every run that
+/// is played to the end is sent as a `RunRecordedEvent`.
+pub struct ReplayPlugin;
+
+impl Plugin for ReplayPlugin {
+    fn build(&self, app: &mut App) {
+        let recorded = match &*app.world.get_resource_or_insert_with(ReplayMode::default) {
+            ReplayMode::Play { replay, .. } => Some(replay.difficulty),
+            _ => None,
+        };
+
+        if let Some(recorded) = recorded {
+            let difficulty = *app.world.get_resource_or_insert_with(Difficulty::default);
+            if difficulty != recorded {
+                warn!(
+                    "The replay was recorded on {:?} but the game is set to {:?}, it will not play out the same",
+                    recorded, difficulty
+                );
+            }
+        }
+
+        app.insert_resource(Recording(None))
+            .add_event::<RunRecordedEvent>()
+            .add_system_to_stage(CoreStage::First, playback_system.after(CoreSystem::Time))
+            .add_system_set(GameSystem::Presentation.set().with_system(record_system));
+    }
+}
+
+/// A run as played: the seed and difficulty it was played with, and the length of every frame
+/// from the one starting it, along with whether the player tapped during it.
+#[derive(Serialize, Deserialize, Clone)]
+pub struct Replay {
+    pub run_seed: u64,
+    pub difficulty: Difficulty,
+    /// the score the run ended with, checked against the one reached when played back
+    pub score: u32,
+    /// index of the frame play began in, after the get ready countdown
+    #[serde(default)]
+    pub play_frame: Option<usize>,
+    pub frames: Vec<ReplayFrame>,
+}
+
+#[derive(Serialize, Deserialize, Clone, Copy)]
+pub struct ReplayFrame {
+    /// length of the frame, in nanoseconds so it is played back exactly
+    pub delta: u64,
+    pub tap: bool,
+}
+
+impl Replay {
+    pub fn load(path: &std::path::Path) -> Result<Self, String> {
+        let contents = std::fs::read_to_string(path).map_err(|err| err.to_string())?;
+        ron::from_str(&contents).map_err(|err| err.to_string())
+    }
+
+    pub fn save(&self, path: &std::path::Path) -> Result<(), String> {
+        let contents = ron::to_string(self).map_err(|err| err.to_string())?;
+        write_save_file(path, &contents)
+    }
+}
+
+pub enum ReplayMode {
+    Off,
+    /// every run is written to this file when it ends, replacing the one before
+    Record(PathBuf),
+    /// the replay is played as soon as the start screen is up, after which the player takes
+    /// over, or the app exits
+    Play {
+        replay: Replay,
+        exit_when_done: bool,
+    },
+}
+
+impl Default for ReplayMode {
+    fn default() -> Self {
+        ReplayMode::Off
+    }
+}
+
+/// A run was played to the end, recorded in full.
+pub struct RunRecordedEvent {
+    pub replay: Replay,
+    /// whether the run was played by a script rather than the player
+    pub scripted: bool,
+}
+
+/// The run being recorded, if any.
+struct Recording(Option<Replay>);
+
+enum Playback {
+    Waiting,
+    /// index of the frame to play next
+    Frame(usize),
+    Done,
+}
+
+impl Default for Playback {
+    fn default() -> Self {
+        Playback::Waiting
+    }
+}
+
+/// Starts, ends and steps the clock of a replay, tapping in the frames the player tapped in.
+/// Runs before anything reads the clock or the taps in a frame.
+fn playback_system(
+    mut playback: Local<Playback>,
+    mut driving_clock: Local<bool>,
+    mode: Res<ReplayMode>,
+    time: Res<Time>,
+    game_state: Res<GameState>,
+    score: Res<Score>,
+    mut clock: ResMut<GameClock>,
+    mut rng: ResMut<GameRng>,
+    mut control: ResMut<PlayerControl>,
+    mut start_new_events: EventWriter<StartNewGameEvent>,
+    mut app_exit_events: EventWriter<AppExit>,
+) {
+    // a replay switched to starts from the start screen, one switched away from hands the
+    // controls back
+    if mode.is_changed() {
+        if matches!(*playback, Playback::Frame(_)) {
+            control.scripted = false;
+            control.tap = false;
+        }
+        *playback = Playback::Waiting;
+    }
+
+    // the clock follows the frames of the replay instead of the wall clock
+    let playing = matches!(*mode, ReplayMode::Play { .. });
+    if playing != *driving_clock {
+        clock.set_manual(playing);
+        *driving_clock = playing;
+    }
+
+    let (replay, exit_when_done) = match &*mode {
+        ReplayMode::Play {
+            replay,
+            exit_when_done,
+        } => (replay, *exit_when_done),
+        _ => return,
+    };
+
+    if matches!(*playback, Playback::Waiting) && crate::game_state::is_start_screen(&game_state) {
+        info!(
+            "Playing back a run of {} frames with seed {}",
+            replay.frames.len(),
+            replay.run_seed
+        );
+
+        rng.set_next_run_seed(replay.run_seed);
+        start_new_events.send(StartNewGameEvent);
+        control.scripted = true;
+        *playback = Playback::Frame(0);
+    }
+
+    match *playback {
+        Playback::Frame(index) if index < replay.frames.len() => {
+            let frame = replay.frames[index];
+
+            clock.advance(Duration::from_nanos(frame.delta));
+            control.tap = frame.tap;
+            *playback = Playback::Frame(index + 1);
+        }
+        Playback::Frame(_) => {
+            if game_state.0 != GameStateType::GameOver {
+                warn!(
+                    "The replay ended before the run did, the game does not play it out the same"
+                );
+            } else if score.0 != replay.score {
+                warn!(
+                    "The replay scored {} instead of the recorded {}",
+                    score.0, replay.score
+                );
+            } else {
+                info!("The replay scored {} as recorded", score.0);
+            }
+
+            control.scripted = false;
+            control.tap = false;
+            *playback = Playback::Done;
+
+            if exit_when_done {
+                app_exit_events.send(AppExit);
+            }
+            clock.advance(time.delta());
+        }
+        Playback::Waiting | Playback::Done => clock.advance(time.delta()),
+    }
+}
+
+/// Records every frame from the one a run is started in up to the one it ends in, and sends the
+/// run once it is over, saving it too when recording to a file.
+fn record_system(
+    mode: Res<ReplayMode>,
+    time: Res<GameClock>,
+    rng: Res<GameRng>,
+    difficulty: Res<Difficulty>,
+    score: Res<Score>,
+    control: Res<PlayerControl>,
+    mut recording: ResMut<Recording>,
+    mut start_new_events: EventReader<StartNewGameEvent>,
+    mut flapped_events: EventReader<PlayerFlappedEvent>,
+    mut game_state_changed_events: EventReader<OnGameStateChangedEvent>,
+    mut recorded_events: EventWriter<RunRecordedEvent>,
+) {
+    if start_new_events.iter().count() > 0 {
+        recording.0 = Some(Replay {
+            run_seed: rng.run_seed(),
+            difficulty: *difficulty,
+            score: 0,
+            play_frame: None,
+            frames: vec![],
+        });
+    }
+
+    let replay = match &mut recording.0 {
+        Some(replay) => replay,
+        None => return,
+    };
+
+    replay.frames.push(ReplayFrame {
+        delta: time.delta().as_nanos() as u64,
+        tap: flapped_events.iter().count() > 0,
+    });
+
+    let state_changes: Vec<GameStateType> = game_state_changed_events
+        .iter()
+        .map(|event| event.0)
+        .collect();
+
+    if state_changes.contains(&GameStateType::Playing) {
+        replay.play_frame = Some(replay.frames.len() - 1);
+    }
+
+    if state_changes.contains(&GameStateType::GameOver) {
+        replay.score = score.0;
+
+        if let ReplayMode::Record(path) = &*mode {
+            match replay.save(path) {
+                Ok(()) => info!("Saved the run to {}", path.display()),
+                Err(err) => error!("Cannot save the run to {}: {}", path.display(), err),
+            }
+        }
+        if let Some(replay) = recording.0.take() {
+            recorded_events.send(RunRecordedEvent {
+                replay,
+                scripted: control.scripted,
+            });
+        }
+    } else if state_changes.iter().any(|state| state.is_menu()) {
+        // left for the menus without finishing the run
+        recording.0 = None;
+    }
+}
@@ -1,10 +1,14 @@
-use crate::{game_state::StartNewGameEvent, player::PlayerCrossedPillarEvent};
+use crate::{
+    game_core::{GameSystem, ResolveSystem},
+    game_state::{GameStateSystem, ResetGameEvent, StartNewGameEvent},
+    player::PlayerCrossedPillarEvent,
+};
 use bevy::prelude::*;
 
-pub struct Score(u32);
+pub struct Score(pub u32);
 
-pub struct IncreaseScoreEvent;
 struct ResetScoreEvent;
+#[derive(Clone)]
 pub struct ScoreUpdatedEvent(pub u32);
 
 pub struct ScorePlugin;
@@ -12,42 +16,43 @@ pub struct ScorePlugin;
 impl Plugin for ScorePlugin {
     fn build(&self, app: &mut App) {
         app.insert_resource(Score(0))
-            .add_event::<IncreaseScoreEvent>()
             .add_event::<ResetScoreEvent>()
             .add_event::<ScoreUpdatedEvent>()
-            .add_system(scoring_system)
-            .add_system(new_game_system)
-            .add_system(score_event_handler_system);
+            .add_system_set(
+                GameSystem::State
+                    .set()
+                    .with_system(new_game_system.after(GameStateSystem::Transition)),
+            )
+            // the score is final by the time a death of the same frame is acted upon
+            .add_system_set(
+                GameSystem::Scoring.set().with_system(
+                    score_event_handler_system
+                        .label(ResolveSystem::Score)
+                        .after(ResolveSystem::Run),
+                ),
+            );
     }
 }
 
-fn scoring_system(
-    mut crossed_event: EventReader<PlayerCrossedPillarEvent>,
-    mut increase_score_events: EventWriter<IncreaseScoreEvent>,
-) {
-    crossed_event.iter().for_each(|_| {
-        increase_score_events.send(IncreaseScoreEvent);
-    });
-}
-
 fn new_game_system(
     mut new_game_events: EventReader<StartNewGameEvent>,
+    mut reset_game_events: EventReader<ResetGameEvent>,
     mut reset_score_event: EventWriter<ResetScoreEvent>,
 ) {
-    if new_game_events.iter().count() > 0 {
+    if new_game_events.iter().count() + reset_game_events.iter().count() > 0 {
         reset_score_event.send(ResetScoreEvent);
     }
 }
 
 fn score_event_handler_system(
     mut score: ResMut<Score>,
-    mut increase_score_events: EventReader<IncreaseScoreEvent>,
+    mut crossed_events: EventReader<PlayerCrossedPillarEvent>,
     mut reset_score_events: EventReader<ResetScoreEvent>,
     mut score_updated_events: EventWriter<ScoreUpdatedEvent>,
 ) {
     let old_score = score.0;
 
-    increase_score_events.iter().for_each(|_| {
+    crossed_events.iter().for_each(|_| {
         score.0 += 1;
     });
 
@@ -1,80 +1,313 @@
-use bevy::prelude::*;
+use bevy::{asset::AssetServerSettings, prelude::*};
+use serde::Deserialize;
 
-use crate::game_state::{GameState, GameStateType, OnGameStateChangedEvent, StartNewGameEvent};
+use crate::{
+    game_assets::read_ron_asset,
+    game_core::{GameClock, GameSystem, ResolveSystem},
+    game_state::{
+        GameState, GameStateType, OnGameStateChangedEvent, OpenMenuEvent, StartNewGameEvent,
+    },
+    locale::LocalizedText,
+    player::{DeathCause, PlayerControl, PlayerKilledEvent},
+    profile::{Profile, ProfileSystem},
+    score::Score,
+    theme::PaletteColor,
+    widgets::{self, ButtonClickedEvent, UiTheme},
+};
 
 pub struct ScreenEndPlugin;
 
 impl Plugin for ScreenEndPlugin {
     fn build(&self, app: &mut App) {
-        app.add_startup_system(setup)
-            .add_system(game_over_system)
-            .add_system(end_screen_input_system);
+        app.insert_resource(MedalThresholds::default())
+            .insert_resource(BestBeforeRun(0))
+            .insert_resource(LastDeath(None))
+            .add_startup_system(load_medal_thresholds)
+            .add_system_set(GameSystem::Input.set().with_system(end_screen_input_system))
+            .add_system_set(GameSystem::State.set().with_system(best_before_run_system))
+            // remembers how the run ended before the results panel is spawned
+            .add_system_set(
+                GameSystem::Scoring
+                    .set()
+                    .with_system(last_death_system.after(ResolveSystem::Run)),
+            )
+            .add_system_set(
+                GameSystem::Presentation
+                    .set()
+                    // shows the best score the run may have just raised
+                    .with_system(game_over_system.after(ProfileSystem::BestScore))
+                    .with_system(results_panel_animation_system),
+            );
     }
 }
 
+const MEDALS_PATH: &str = "medals.ron";
+const SLIDE_DISTANCE: f32 = 400.0;
+const SLIDE_DURATION: f32 = 0.4;
+
+/// Score needed for each medal, read from `medals.ron`.
+#[derive(Deserialize)]
+struct MedalThresholds {
+    bronze: u32,
+    silver: u32,
+    gold: u32,
+    platinum: u32,
+}
+
+impl Default for MedalThresholds {
+    fn default() -> Self {
+        Self {
+            bronze: 10,
+            silver: 20,
+            gold: 30,
+            platinum: 40,
+        }
+    }
+}
+
+impl MedalThresholds {
+    fn medal(&self, score: u32) -> Option<Medal> {
+        [
+            (self.platinum, Medal::Platinum),
+            (self.gold, Medal::Gold),
+            (self.silver, Medal::Silver),
+            (self.bronze, Medal::Bronze),
+        ]
+        .into_iter()
+        .find(|(threshold, _)| score >= *threshold)
+        .map(|(_, medal)| medal)
+    }
+}
+
+#[derive(Clone, Copy)]
+enum Medal {
+    Bronze,
+    Silver,
+    Gold,
+    Platinum,
+}
+
+impl Medal {
+    fn key(&self) -> &'static str {
+        match self {
+            Medal::Bronze => "medal-bronze",
+            Medal::Silver => "medal-silver",
+            Medal::Gold => "medal-gold",
+            Medal::Platinum => "medal-platinum",
+        }
+    }
+
+    // medals keep their colours whatever the theme
+    fn color(&self) -> Color {
+        match self {
+            Medal::Bronze => Color::rgb(0.8, 0.5, 0.2),
+            Medal::Silver => Color::rgb(0.75, 0.75, 0.75),
+            Medal::Gold => Color::rgb(1.0, 0.84, 0.0),
+            Medal::Platinum => Color::rgb(0.9, 0.95, 1.0),
+        }
+    }
+}
+
+/// The best score at the start of the current run, to tell whether it was beaten.
+struct BestBeforeRun(u32);
+
+/// How the current run ended.
+struct LastDeath(Option<PlayerKilledEvent>);
+
+#[derive(Component)]
+struct ScreenEnd;
+
+/// Slides the results panel into place after the run ended.
 #[derive(Component)]
-struct ScreenEndText;
-
-fn setup(mut commands: Commands, asset_server: Res<AssetServer>) {
-    let font = asset_server.load("FiraSans-Bold.ttf");
-
-    commands
-        .spawn_bundle(TextBundle {
-            text: Text {
-                sections: vec![
-                    TextSection {
-                        value: "Game Over!".to_string(),
-                        style: TextStyle {
-                            font: font.clone(),
-                            font_size: 60.0,
-                            color: Color::RED,
-                        },
-                    },
-                    TextSection {
-                        value: "  Press <R> to restart".to_string(),
-                        style: TextStyle {
-                            font,
-                            font_size: 40.0,
-                            color: Color::BLACK,
-                        },
-                    },
-                ],
-                alignment: TextAlignment {
-                    vertical: VerticalAlign::Center,
-                    horizontal: HorizontalAlign::Center,
-                },
-            },
-            style: Style {
-                position_type: PositionType::Absolute,
-                position: Rect {
-                    top: Val::Percent(50.0),
-                    left: Val::Percent(50.0),
-                    ..Default::default()
-                },
-                ..Default::default()
-            },
-            visibility: Visibility { is_visible: false },
-            ..Default::default()
-        })
-        .insert(ScreenEndText);
+struct ResultsPanel {
+    elapsed: f32,
+}
+
+#[derive(Component, Clone, Copy)]
+enum EndButton {
+    Restart,
+    MainMenu,
+}
+
+fn load_medal_thresholds(
+    settings: Option<Res<AssetServerSettings>>,
+    mut thresholds: ResMut<MedalThresholds>,
+) {
+    match read_ron_asset(settings.as_deref(), MEDALS_PATH) {
+        Ok(loaded) => *thresholds = loaded,
+        Err(err) => error!("Using default medal thresholds, cannot read {}", err),
+    }
+}
+
+fn best_before_run_system(
+    profile: Res<Profile>,
+    mut best_before_run: ResMut<BestBeforeRun>,
+    mut start_new_events: EventReader<StartNewGameEvent>,
+) {
+    if start_new_events.iter().count() > 0 {
+        best_before_run.0 = profile.best_score;
+    }
+}
+
+fn last_death_system(
+    mut last_death: ResMut<LastDeath>,
+    mut start_new_events: EventReader<StartNewGameEvent>,
+    mut killed_events: EventReader<PlayerKilledEvent>,
+) {
+    if start_new_events.iter().count() > 0 {
+        last_death.0 = None;
+    }
+
+    if let Some(event) = killed_events.iter().last() {
+        last_death.0 = Some(event.clone());
+    }
 }
 
 fn game_over_system(
+    mut commands: Commands,
+    ui_theme: Res<UiTheme>,
+    score: Res<Score>,
+    profile: Res<Profile>,
+    best_before_run: Res<BestBeforeRun>,
+    last_death: Res<LastDeath>,
+    thresholds: Res<MedalThresholds>,
     mut game_status_changed: EventReader<OnGameStateChangedEvent>,
-    mut game_over_query: Query<&mut Visibility, With<ScreenEndText>>,
+    query: Query<Entity, With<ScreenEnd>>,
+) {
+    if let Some(event) = game_status_changed.iter().last() {
+        query
+            .iter()
+            .for_each(|entity| commands.entity(entity).despawn_recursive());
+
+        if matches!(event.0, GameStateType::GameOver) {
+            let new_best = score.0 > best_before_run.0;
+            let medal = thresholds.medal(score.0);
+
+            widgets::screen(&mut commands)
+                .insert(ScreenEnd)
+                .with_children(|parent| {
+                    parent
+                        .spawn_bundle(NodeBundle {
+                            style: Style {
+                                flex_direction: FlexDirection::ColumnReverse,
+                                align_items: AlignItems::Center,
+                                padding: Rect::all(Val::Px(24.0)),
+                                position_type: PositionType::Relative,
+                                position: Rect {
+                                    top: Val::Px(SLIDE_DISTANCE),
+                                    ..Default::default()
+                                },
+                                ..Default::default()
+                            },
+                            color: UiColor(ui_theme.text_color.clone().set_a(0.15).as_rgba()),
+                            ..Default::default()
+                        })
+                        .insert(ResultsPanel { elapsed: 0.0 })
+                        .with_children(|parent| {
+                            widgets::title(parent, &ui_theme, "game-over-title");
+
+                            if let Some(medal) = medal {
+                                parent.spawn_bundle(NodeBundle {
+                                    style: Style {
+                                        size: Size::new(Val::Px(64.0), Val::Px(64.0)),
+                                        margin: Rect::all(Val::Px(8.0)),
+                                        ..Default::default()
+                                    },
+                                    color: UiColor(medal.color()),
+                                    ..Default::default()
+                                });
+                                widgets::label(parent, &ui_theme, medal.key());
+                            }
+
+                            widgets::label(
+                                parent,
+                                &ui_theme,
+                                LocalizedText::new("game-over-score").with_arg("score", score.0),
+                            );
+                            widgets::label(
+                                parent,
+                                &ui_theme,
+                                LocalizedText::new("game-over-best")
+                                    .with_arg("best", profile.best_score),
+                            );
+
+                            if let Some(death) = &last_death.0 {
+                                widgets::label(
+                                    parent,
+                                    &ui_theme,
+                                    LocalizedText::new(match death.cause {
+                                        DeathCause::TopPillar => "death-top-pillar",
+                                        DeathCause::BottomPillar => "death-bottom-pillar",
+                                        DeathCause::Ceiling => "death-ceiling",
+                                        DeathCause::Floor => "death-floor",
+                                    })
+                                    .with_arg("time", format!("{:.1}", death.run_time)),
+                                );
+                            }
+
+                            if new_best {
+                                widgets::text(
+                                    parent,
+                                    &ui_theme,
+                                    "game-over-new-best",
+                                    ui_theme.label_size,
+                                    PaletteColor::Highlight,
+                                );
+                            }
+
+                            widgets::menu(parent).with_children(|parent| {
+                                widgets::button(parent, &ui_theme, "game-over-restart")
+                                    .insert(EndButton::Restart);
+                                widgets::button(parent, &ui_theme, "game-over-main-menu")
+                                    .insert(EndButton::MainMenu);
+                            });
+                        });
+                });
+        }
+    }
+}
+
+fn results_panel_animation_system(
+    time: Res<GameClock>,
+    mut query: Query<(&mut ResultsPanel, &mut Style)>,
 ) {
-    game_status_changed.iter().for_each(|event| {
-        let mut game_over_visibility = game_over_query.single_mut();
-        game_over_visibility.is_visible = matches!(event.0, GameStateType::GameOver);
+    query.iter_mut().for_each(|(mut panel, mut style)| {
+        if panel.elapsed >= SLIDE_DURATION {
+            return;
+        }
+
+        panel.elapsed = (panel.elapsed + time.delta_seconds()).min(SLIDE_DURATION);
+
+        // ease out, so the panel slows down as it settles
+        let remaining = 1.0 - panel.elapsed / SLIDE_DURATION;
+        style.position.top = Val::Px(SLIDE_DISTANCE * remaining * remaining);
     });
 }
 
 fn end_screen_input_system(
     game_state: Res<GameState>,
+    control: Res<PlayerControl>,
     keyboard_input: Res<Input<KeyCode>>,
+    button_query: Query<&EndButton>,
+    mut clicked_events: EventReader<ButtonClickedEvent>,
     mut start_new_events: EventWriter<StartNewGameEvent>,
+    mut open_menu_events: EventWriter<OpenMenuEvent>,
 ) {
-    if crate::game_state::is_game_over(&game_state) && keyboard_input.just_pressed(KeyCode::R) {
+    let clicked = clicked_events
+        .iter()
+        .filter_map(|event| button_query.get(event.0).ok())
+        .last()
+        .copied();
+
+    if !crate::game_state::is_game_over(&game_state) || control.scripted {
+        return;
+    }
+
+    if keyboard_input.just_pressed(KeyCode::R) || matches!(clicked, Some(EndButton::Restart)) {
         start_new_events.send(StartNewGameEvent);
+    } else if keyboard_input.just_pressed(KeyCode::Escape)
+        || matches!(clicked, Some(EndButton::MainMenu))
+    {
+        open_menu_events.send(OpenMenuEvent(GameStateType::StartScreen));
     }
 }
@@ -0,0 +1,99 @@
+use bevy::prelude::*;
+
+use crate::{
+    game_core::{GameClock, GameSystem},
+    game_state::{BeginPlayEvent, GameState, GameStateType, OnGameStateChangedEvent},
+    locale::LocalizedText,
+    player::PlayerControl,
+    theme::PaletteColor,
+    widgets::{self, UiTheme},
+};
+
+pub struct ScreenGetReadyPlugin;
+
+impl Plugin for ScreenGetReadyPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_system_set(GameSystem::Input.set().with_system(countdown_system))
+            .add_system_set(GameSystem::Presentation.set().with_system(screen_system));
+    }
+}
+
+const COUNTDOWN_SECONDS: f32 = 3.0;
+
+#[derive(Component)]
+struct GetReadyScreen;
+
+#[derive(Component)]
+struct Countdown(Timer);
+
+fn screen_system(
+    mut commands: Commands,
+    ui_theme: Res<UiTheme>,
+    mut game_state_changed_events: EventReader<OnGameStateChangedEvent>,
+    query: Query<Entity, With<GetReadyScreen>>,
+) {
+    if let Some(event) = game_state_changed_events.iter().last() {
+        query
+            .iter()
+            .for_each(|entity| commands.entity(entity).despawn_recursive());
+
+        if matches!(event.0, GameStateType::GetReady) {
+            widgets::screen(&mut commands)
+                .insert(GetReadyScreen)
+                .with_children(|parent| {
+                    widgets::title(parent, &ui_theme, "get-ready-title");
+                    widgets::text(
+                        parent,
+                        &ui_theme,
+                        LocalizedText::new("get-ready-countdown")
+                            .with_arg("seconds", COUNTDOWN_SECONDS as u32),
+                        ui_theme.title_size,
+                        PaletteColor::Highlight,
+                    )
+                    .insert(Countdown(Timer::from_seconds(COUNTDOWN_SECONDS, false)));
+                    widgets::label(parent, &ui_theme, "get-ready-hint");
+                });
+        }
+    }
+}
+
+fn countdown_system(
+    time: Res<GameClock>,
+    game_state: Res<GameState>,
+    control: Res<PlayerControl>,
+    keyboard_input: Res<Input<KeyCode>>,
+    mouse_input: Res<Input<MouseButton>>,
+    gamepad_input: Res<Input<GamepadButton>>,
+    mut query: Query<(&mut Countdown, &mut LocalizedText)>,
+    mut begin_play_events: EventWriter<BeginPlayEvent>,
+) {
+    if !crate::game_state::is_get_ready(&game_state) {
+        return;
+    }
+
+    // the countdown only exists from the frame after the state changed, so the
+    // press that started the game is never mistaken for the first tap
+    let (mut countdown, mut text) = match query.get_single_mut() {
+        Ok(countdown) => countdown,
+        Err(_) => return,
+    };
+
+    let tapped = if control.scripted {
+        control.tap
+    } else {
+        keyboard_input.just_pressed(KeyCode::Space)
+            || mouse_input.just_pressed(MouseButton::Left)
+            || gamepad_input
+                .get_just_pressed()
+                .any(|button| button.1 == GamepadButtonType::South)
+    };
+
+    if tapped {
+        begin_play_events.send(BeginPlayEvent { flap: true });
+    } else if countdown.0.tick(time.delta()).just_finished() {
+        begin_play_events.send(BeginPlayEvent { flap: false });
+    } else {
+        let remaining = (COUNTDOWN_SECONDS - countdown.0.elapsed_secs()).ceil();
+        text.set_arg("seconds", remaining as u32);
+    }
+}
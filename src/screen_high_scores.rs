@@ -0,0 +1,88 @@
+use bevy::prelude::*;
+
+use crate::{
+    game_core::GameSystem,
+    game_state::{GameState, GameStateType, OnGameStateChangedEvent, OpenMenuEvent},
+    locale::LocalizedText,
+    profile::Profile,
+    widgets::{self, ButtonClickedEvent, UiTheme},
+};
+
+pub struct ScreenHighScoresPlugin;
+
+impl Plugin for ScreenHighScoresPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_system_set(GameSystem::Input.set().with_system(high_scores_menu_system))
+            .add_system_set(GameSystem::Presentation.set().with_system(screen_system));
+    }
+}
+
+#[derive(Component)]
+struct HighScoresScreen;
+
+#[derive(Component)]
+struct BackButton;
+
+fn screen_system(
+    mut commands: Commands,
+    ui_theme: Res<UiTheme>,
+    profile: Res<Profile>,
+    mut game_state_changed_events: EventReader<OnGameStateChangedEvent>,
+    query: Query<Entity, With<HighScoresScreen>>,
+) {
+    if let Some(event) = game_state_changed_events.iter().last() {
+        query
+            .iter()
+            .for_each(|entity| commands.entity(entity).despawn_recursive());
+
+        if matches!(event.0, GameStateType::HighScores) {
+            widgets::screen(&mut commands)
+                .insert(HighScoresScreen)
+                .with_children(|parent| {
+                    widgets::title(parent, &ui_theme, "high-scores-title");
+
+                    if profile.high_scores.is_empty() {
+                        widgets::label(parent, &ui_theme, "high-scores-empty");
+                    }
+
+                    profile
+                        .high_scores
+                        .iter()
+                        .enumerate()
+                        .for_each(|(index, score)| {
+                            widgets::label(
+                                parent,
+                                &ui_theme,
+                                LocalizedText::new("high-scores-entry")
+                                    .with_arg("rank", index + 1)
+                                    .with_arg("score", score),
+                            );
+                        });
+
+                    widgets::menu(parent).with_children(|parent| {
+                        widgets::button(parent, &ui_theme, "back").insert(BackButton);
+                    });
+                });
+        }
+    }
+}
+
+fn high_scores_menu_system(
+    game_state: Res<GameState>,
+    keyboard_input: Res<Input<KeyCode>>,
+    button_query: Query<&BackButton>,
+    mut clicked_events: EventReader<ButtonClickedEvent>,
+    mut open_menu_events: EventWriter<OpenMenuEvent>,
+) {
+    if !matches!(game_state.0, GameStateType::HighScores) {
+        return;
+    }
+
+    let back_clicked = clicked_events
+        .iter()
+        .any(|event| button_query.get(event.0).is_ok());
+
+    if back_clicked || keyboard_input.just_pressed(KeyCode::Escape) {
+        open_menu_events.send(OpenMenuEvent(GameStateType::StartScreen));
+    }
+}
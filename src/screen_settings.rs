@@ -0,0 +1,171 @@
+use bevy::prelude::*;
+
+use crate::{
+    game_core::{GameSystem, ScalingMode},
+    game_state::{GameState, GameStateType, OnGameStateChangedEvent, OpenMenuEvent},
+    locale::{Locale, LocalizedText},
+    profile::Profile,
+    theme::{CycleThemeEvent, Themes},
+    widgets::{self, ButtonClickedEvent, ButtonLabel, UiTheme},
+};
+
+pub struct ScreenSettingsPlugin;
+
+impl Plugin for ScreenSettingsPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_system_set(GameSystem::Input.set().with_system(settings_menu_system))
+            .add_system_set(
+                GameSystem::Presentation
+                    .set()
+                    .with_system(screen_system)
+                    .with_system(settings_label_system),
+            );
+    }
+}
+
+#[derive(Component)]
+struct SettingsScreen;
+
+#[derive(Component)]
+struct UnlockLabel;
+
+#[derive(Component, Clone, Copy)]
+enum SettingsButton {
+    Theme,
+    Scaling,
+    Language,
+    Back,
+}
+
+fn screen_system(
+    mut commands: Commands,
+    ui_theme: Res<UiTheme>,
+    mut game_state_changed_events: EventReader<OnGameStateChangedEvent>,
+    query: Query<Entity, With<SettingsScreen>>,
+) {
+    if let Some(event) = game_state_changed_events.iter().last() {
+        query
+            .iter()
+            .for_each(|entity| commands.entity(entity).despawn_recursive());
+
+        if matches!(event.0, GameStateType::Settings) {
+            widgets::screen(&mut commands)
+                .insert(SettingsScreen)
+                .with_children(|parent| {
+                    widgets::title(parent, &ui_theme, "settings-title");
+
+                    // the arguments are filled in by settings_label_system
+                    widgets::menu(parent).with_children(|parent| {
+                        [
+                            (SettingsButton::Theme, "settings-theme"),
+                            (SettingsButton::Scaling, "settings-scaling"),
+                            (SettingsButton::Language, "settings-language"),
+                            (SettingsButton::Back, "back"),
+                        ]
+                        .into_iter()
+                        .for_each(|(button, key)| {
+                            widgets::button(parent, &ui_theme, key).insert(button);
+                        });
+                    });
+
+                    widgets::label(parent, &ui_theme, "").insert(UnlockLabel);
+                });
+        }
+    }
+}
+
+fn settings_menu_system(
+    game_state: Res<GameState>,
+    keyboard_input: Res<Input<KeyCode>>,
+    mut profile: ResMut<Profile>,
+    mut locale: ResMut<Locale>,
+    button_query: Query<&SettingsButton>,
+    mut clicked_events: EventReader<ButtonClickedEvent>,
+    mut cycle_theme_events: EventWriter<CycleThemeEvent>,
+    mut open_menu_events: EventWriter<OpenMenuEvent>,
+) {
+    if !matches!(game_state.0, GameStateType::Settings) {
+        return;
+    }
+
+    if keyboard_input.just_pressed(KeyCode::Escape) {
+        open_menu_events.send(OpenMenuEvent(GameStateType::StartScreen));
+    }
+
+    clicked_events
+        .iter()
+        .filter_map(|event| button_query.get(event.0).ok())
+        .for_each(|button| match button {
+            SettingsButton::Theme => cycle_theme_events.send(CycleThemeEvent),
+            SettingsButton::Scaling => {
+                profile.scaling = match profile.scaling {
+                    ScalingMode::Letterbox => ScalingMode::Fit,
+                    ScalingMode::Fit => ScalingMode::Stretch,
+                    ScalingMode::Stretch => ScalingMode::Letterbox,
+                };
+            }
+            SettingsButton::Language => {
+                profile.language = locale.next_language().to_string();
+            }
+            SettingsButton::Back => {
+                open_menu_events.send(OpenMenuEvent(GameStateType::StartScreen))
+            }
+        });
+}
+
+fn settings_label_system(
+    themes: Res<Themes>,
+    profile: Res<Profile>,
+    locale: Res<Locale>,
+    added_query: Query<(), Added<SettingsButton>>,
+    button_query: Query<(&SettingsButton, &Children)>,
+    mut label_query: Query<&mut LocalizedText, (With<ButtonLabel>, Without<UnlockLabel>)>,
+    mut unlock_query: Query<&mut LocalizedText, (With<UnlockLabel>, Without<ButtonLabel>)>,
+) {
+    if !(themes.is_changed()
+        || profile.is_changed()
+        || locale.is_changed()
+        || !added_query.is_empty())
+    {
+        return;
+    }
+
+    button_query.iter().for_each(|(button, children)| {
+        let (name, value) = match button {
+            SettingsButton::Theme => (
+                "theme",
+                themes.active().map_or(String::new(), |theme| {
+                    let key = format!("theme-{}", theme.id);
+                    if locale.contains(&key) {
+                        locale.get(&key)
+                    } else {
+                        theme.name.clone()
+                    }
+                }),
+            ),
+            SettingsButton::Scaling => (
+                "mode",
+                locale.get(match profile.scaling {
+                    ScalingMode::Letterbox => "scaling-letterbox",
+                    ScalingMode::Fit => "scaling-fit",
+                    ScalingMode::Stretch => "scaling-stretch",
+                }),
+            ),
+            SettingsButton::Language => ("language", locale.language_name()),
+            SettingsButton::Back => return,
+        };
+
+        children.iter().for_each(|child| {
+            if let Ok(mut text) = label_query.get_mut(*child) {
+                text.set_arg(name, &value);
+            }
+        });
+    });
+
+    unlock_query.iter_mut().for_each(|mut text| {
+        *text = match themes.next_unlock(profile.best_score) {
+            Some(score) => LocalizedText::new("settings-unlock").with_arg("score", score),
+            None => LocalizedText::new("settings-all-unlocked"),
+        };
+    });
+}
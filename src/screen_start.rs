@@ -1,79 +1,93 @@
-use bevy::prelude::*;
+use bevy::{app::AppExit, prelude::*};
 
 use crate::{
-    game_state::{GameState, StartNewGameEvent},
-    loading::FinishLoadingEvent,
+    game_core::GameSystem,
+    game_state::{
+        GameState, GameStateType, OnGameStateChangedEvent, OpenMenuEvent, StartNewGameEvent,
+    },
+    widgets::{self, ButtonClickedEvent, UiTheme},
 };
 
 pub struct ScreenStartPlugin;
 
 impl Plugin for ScreenStartPlugin {
     fn build(&self, app: &mut App) {
-        app.add_startup_system(setup)
-            .add_system(load_finish_system)
-            .add_system(start_screen_input_system);
+        app.add_system_set(GameSystem::Input.set().with_system(main_menu_system))
+            .add_system_set(GameSystem::Presentation.set().with_system(screen_system));
     }
 }
 
 #[derive(Component)]
-struct StartScreenText;
+struct StartScreen;
 
-fn setup(mut commands: Commands, asset_server: Res<AssetServer>) {
-    let font = asset_server.load("FiraSans-Bold.ttf");
-
-    commands
-        .spawn_bundle(TextBundle {
-            text: Text {
-                sections: vec![TextSection {
-                    value: "Press <Space> to start".to_string(),
-                    style: TextStyle {
-                        font,
-                        font_size: 60.0,
-                        color: Color::BLACK,
-                    },
-                }],
-                alignment: TextAlignment {
-                    vertical: VerticalAlign::Center,
-                    horizontal: HorizontalAlign::Center,
-                },
-            },
-            style: Style {
-                position_type: PositionType::Absolute,
-                position: Rect {
-                    top: Val::Percent(50.0),
-                    left: Val::Percent(50.0),
-                    ..Default::default()
-                },
-                ..Default::default()
-            },
-            visibility: Visibility { is_visible: false },
-            ..Default::default()
-        })
-        .insert(StartScreenText);
+#[derive(Component, Clone, Copy)]
+enum MainMenuButton {
+    Play,
+    Settings,
+    HighScores,
+    Stats,
+    Quit,
 }
 
-fn load_finish_system(
-    mut finish_loading_events: EventReader<FinishLoadingEvent>,
-    mut query: Query<&mut Visibility, With<StartScreenText>>,
+fn screen_system(
+    mut commands: Commands,
+    ui_theme: Res<UiTheme>,
+    mut game_state_changed_events: EventReader<OnGameStateChangedEvent>,
+    query: Query<Entity, With<StartScreen>>,
 ) {
-    if finish_loading_events.iter().count() > 0 {
-        let mut visibility = query.single_mut();
-        visibility.is_visible = true;
+    if let Some(event) = game_state_changed_events.iter().last() {
+        query
+            .iter()
+            .for_each(|entity| commands.entity(entity).despawn_recursive());
+
+        if matches!(event.0, GameStateType::StartScreen) {
+            widgets::screen(&mut commands)
+                .insert(StartScreen)
+                .with_children(|parent| {
+                    widgets::title(parent, &ui_theme, "app-title");
+
+                    widgets::menu(parent).with_children(|parent| {
+                        [
+                            (MainMenuButton::Play, "menu-play"),
+                            (MainMenuButton::Settings, "menu-settings"),
+                            (MainMenuButton::HighScores, "menu-high-scores"),
+                            (MainMenuButton::Stats, "menu-stats"),
+                            (MainMenuButton::Quit, "menu-quit"),
+                        ]
+                        .into_iter()
+                        .for_each(|(button, value)| {
+                            widgets::button(parent, &ui_theme, value).insert(button);
+                        });
+                    });
+                });
+        }
     }
 }
 
-fn start_screen_input_system(
+fn main_menu_system(
     game_state: Res<GameState>,
-    keyboard_input: Res<Input<KeyCode>>,
-    mut query: Query<&mut Visibility, With<StartScreenText>>,
+    button_query: Query<&MainMenuButton>,
+    mut clicked_events: EventReader<ButtonClickedEvent>,
     mut start_new_events: EventWriter<StartNewGameEvent>,
+    mut open_menu_events: EventWriter<OpenMenuEvent>,
+    mut app_exit_events: EventWriter<AppExit>,
 ) {
-    if crate::game_state::is_start_screen(&game_state)
-        && keyboard_input.just_pressed(KeyCode::Space)
-    {
-        start_new_events.send(StartNewGameEvent);
-
-        let mut visibility = query.single_mut();
-        visibility.is_visible = false;
+    if !crate::game_state::is_start_screen(&game_state) {
+        return;
     }
+
+    clicked_events
+        .iter()
+        .filter_map(|event| button_query.get(event.0).ok())
+        .for_each(|button| match button {
+            MainMenuButton::Play => start_new_events.send(StartNewGameEvent),
+            MainMenuButton::Settings => {
+                open_menu_events.send(OpenMenuEvent(GameStateType::Settings))
+            }
+            MainMenuButton::HighScores => {
+                open_menu_events.send(OpenMenuEvent(GameStateType::HighScores))
+            }
+            MainMenuButton::Stats => open_menu_events.send(OpenMenuEvent(GameStateType::Stats)),
+            MainMenuButton::Quit => app_exit_events.send(AppExit),
+        });
 }
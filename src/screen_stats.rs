@@ -0,0 +1,93 @@
+use bevy::prelude::*;
+
+use crate::{
+    game_core::GameSystem,
+    game_state::{GameState, GameStateType, OnGameStateChangedEvent, OpenMenuEvent},
+    locale::LocalizedText,
+    stats::Stats,
+    widgets::{self, ButtonClickedEvent, UiTheme},
+};
+
+pub struct ScreenStatsPlugin;
+
+impl Plugin for ScreenStatsPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_system_set(GameSystem::Input.set().with_system(stats_menu_system))
+            .add_system_set(GameSystem::Presentation.set().with_system(screen_system));
+    }
+}
+
+#[derive(Component)]
+struct StatsScreen;
+
+#[derive(Component)]
+struct BackButton;
+
+fn screen_system(
+    mut commands: Commands,
+    ui_theme: Res<UiTheme>,
+    stats: Res<Stats>,
+    mut game_state_changed_events: EventReader<OnGameStateChangedEvent>,
+    query: Query<Entity, With<StatsScreen>>,
+) {
+    if let Some(event) = game_state_changed_events.iter().last() {
+        query
+            .iter()
+            .for_each(|entity| commands.entity(entity).despawn_recursive());
+
+        if matches!(event.0, GameStateType::Stats) {
+            let session = stats.longest_session as u32;
+
+            let lines = [
+                LocalizedText::new("stats-games").with_arg("count", stats.games_played),
+                LocalizedText::new("stats-pillars").with_arg("count", stats.pillars_crossed),
+                LocalizedText::new("stats-flaps").with_arg("count", stats.flaps),
+                LocalizedText::new("stats-average")
+                    .with_arg("score", format!("{:.1}", stats.average_score())),
+                LocalizedText::new("stats-median").with_arg("score", stats.median_score()),
+                LocalizedText::new("stats-longest-session")
+                    .with_arg("time", format!("{}:{:02}", session / 60, session % 60)),
+                LocalizedText::new("stats-deaths-top-pillar")
+                    .with_arg("count", stats.deaths.top_pillar),
+                LocalizedText::new("stats-deaths-bottom-pillar")
+                    .with_arg("count", stats.deaths.bottom_pillar),
+                LocalizedText::new("stats-deaths-ceiling").with_arg("count", stats.deaths.ceiling),
+                LocalizedText::new("stats-deaths-floor").with_arg("count", stats.deaths.floor),
+            ];
+
+            widgets::screen(&mut commands)
+                .insert(StatsScreen)
+                .with_children(|parent| {
+                    widgets::title(parent, &ui_theme, "stats-title");
+
+                    lines.into_iter().for_each(|line| {
+                        widgets::label(parent, &ui_theme, line);
+                    });
+
+                    widgets::menu(parent).with_children(|parent| {
+                        widgets::button(parent, &ui_theme, "back").insert(BackButton);
+                    });
+                });
+        }
+    }
+}
+
+fn stats_menu_system(
+    game_state: Res<GameState>,
+    keyboard_input: Res<Input<KeyCode>>,
+    button_query: Query<&BackButton>,
+    mut clicked_events: EventReader<ButtonClickedEvent>,
+    mut open_menu_events: EventWriter<OpenMenuEvent>,
+) {
+    if !matches!(game_state.0, GameStateType::Stats) {
+        return;
+    }
+
+    let back_clicked = clicked_events
+        .iter()
+        .any(|event| button_query.get(event.0).is_ok());
+
+    if back_clicked || keyboard_input.just_pressed(KeyCode::Escape) {
+        open_menu_events.send(OpenMenuEvent(GameStateType::StartScreen));
+    }
+}
@@ -0,0 +1,206 @@
+use std::collections::BTreeMap;
+
+use bevy::prelude::*;
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    game_core::{GameClock, GameSystem},
+    game_state::{GameState, GameStateType, OnGameStateChangedEvent},
+    player::{
+        DeathCause, PlayerControl, PlayerCrossedPillarEvent, PlayerFlappedEvent, PlayerKilledEvent,
+    },
+    profile::{save_path, write_save_file, SaveSettings},
+    score::Score,
+};
+
+pub struct StatsPlugin;
+
+impl Plugin for StatsPlugin {
+    fn build(&self, app: &mut App) {
+        let settings = app
+            .world
+            .get_resource_or_insert_with(SaveSettings::default)
+            .clone();
+
+        app.insert_resource(Stats::load(&settings))
+            .insert_resource(SessionTime(0.0))
+            .add_system_set(
+                GameSystem::Presentation
+                    .set()
+                    .with_system(session_time_system)
+                    .with_system(play_stats_system)
+                    .with_system(game_over_stats_system),
+            );
+    }
+}
+
+const STATS_FILE: &str = "stats.ron";
+
+/// Play history across every session, saved next to the profile after each game.
+#[derive(Serialize, Deserialize, Default)]
+#[serde(default)]
+pub struct Stats {
+    pub games_played: u32,
+    pub pillars_crossed: u32,
+    pub flaps: u32,
+    /// how many games ended with each score, to work out the average and median
+    pub scores: BTreeMap<u32, u32>,
+    /// most seconds spent playing between starting and quitting the game
+    pub longest_session: f32,
+    pub deaths: DeathStats,
+}
+
+#[derive(Serialize, Deserialize, Default)]
+#[serde(default)]
+pub struct DeathStats {
+    pub top_pillar: u32,
+    pub bottom_pillar: u32,
+    pub ceiling: u32,
+    pub floor: u32,
+}
+
+impl Stats {
+    fn load(settings: &SaveSettings) -> Self {
+        let path = match save_path(settings, STATS_FILE) {
+            Some(path) => path,
+            None => return Stats::default(),
+        };
+
+        match std::fs::read_to_string(&path) {
+            Ok(contents) => ron::from_str(&contents).unwrap_or_else(|err| {
+                error!("Ignoring unreadable stats {}: {}", path.display(), err);
+                Stats::default()
+            }),
+            Err(_) => Stats::default(),
+        }
+    }
+
+    fn save(&self, settings: &SaveSettings) {
+        let path = match save_path(settings, STATS_FILE) {
+            Some(path) => path,
+            None => return,
+        };
+
+        let result = ron::ser::to_string_pretty(self, Default::default())
+            .map_err(|err| err.to_string())
+            .and_then(|contents| write_save_file(&path, &contents));
+
+        if let Err(err) = result {
+            error!("Cannot save stats {}: {}", path.display(), err);
+        }
+    }
+
+    fn scored_games(&self) -> u32 {
+        self.scores.values().sum()
+    }
+
+    pub fn average_score(&self) -> f32 {
+        let games = self.scored_games();
+        if games == 0 {
+            return 0.0;
+        }
+
+        let total: u32 = self.scores.iter().map(|(score, count)| score * count).sum();
+        total as f32 / games as f32
+    }
+
+    pub fn median_score(&self) -> f32 {
+        let games = self.scored_games();
+        if games == 0 {
+            return 0.0;
+        }
+
+        // the scores at the two middle positions, which are the same one for an odd count
+        let nth = |position: u32| {
+            let mut seen = 0;
+            self.scores
+                .iter()
+                .find(|(_, count)| {
+                    seen += **count;
+                    seen > position
+                })
+                .map_or(0, |(score, _)| *score)
+        };
+
+        (nth((games - 1) / 2) + nth(games / 2)) as f32 / 2.0
+    }
+}
+
+impl DeathStats {
+    fn count(&mut self, cause: DeathCause) {
+        match cause {
+            DeathCause::TopPillar => self.top_pillar += 1,
+            DeathCause::BottomPillar => self.bottom_pillar += 1,
+            DeathCause::Ceiling => self.ceiling += 1,
+            DeathCause::Floor => self.floor += 1,
+        }
+    }
+}
+
+/// Seconds spent playing since the game was started.
+struct SessionTime(f32);
+
+fn session_time_system(
+    time: Res<GameClock>,
+    game_state: Res<GameState>,
+    control: Res<PlayerControl>,
+    mut session_time: ResMut<SessionTime>,
+) {
+    if crate::game_state::is_playing(&game_state) && !control.scripted {
+        session_time.0 += time.delta_seconds();
+    }
+}
+
+fn play_stats_system(
+    control: Res<PlayerControl>,
+    mut stats: ResMut<Stats>,
+    mut crossed_events: EventReader<PlayerCrossedPillarEvent>,
+    mut flapped_events: EventReader<PlayerFlappedEvent>,
+) {
+    let crossed = crossed_events.iter().count() as u32;
+    let flaps = flapped_events.iter().count() as u32;
+
+    if control.scripted {
+        return;
+    }
+
+    if crossed > 0 {
+        stats.pillars_crossed += crossed;
+    }
+    if flaps > 0 {
+        stats.flaps += flaps;
+    }
+}
+
+fn game_over_stats_system(
+    settings: Res<SaveSettings>,
+    score: Res<Score>,
+    session_time: Res<SessionTime>,
+    control: Res<PlayerControl>,
+    mut stats: ResMut<Stats>,
+    mut death_cause: Local<Option<DeathCause>>,
+    mut killed_events: EventReader<PlayerKilledEvent>,
+    mut game_state_changed_events: EventReader<OnGameStateChangedEvent>,
+) {
+    killed_events.iter().for_each(|event| {
+        *death_cause = Some(event.cause);
+    });
+
+    let game_over = game_state_changed_events
+        .iter()
+        .any(|event| matches!(event.0, GameStateType::GameOver));
+
+    if game_over && control.scripted {
+        *death_cause = None;
+    } else if game_over {
+        stats.games_played += 1;
+        if let Some(cause) = death_cause.take() {
+            stats.deaths.count(cause);
+        }
+        *stats.scores.entry(score.0).or_insert(0) += 1;
+        stats.longest_session = stats.longest_session.max(session_time.0);
+
+        // saving once per game rather than on every flap
+        stats.save(&settings);
+    }
+}
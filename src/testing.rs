@@ -0,0 +1,428 @@
+//! A headless `App` running the game plugins, stepped one fixed tick at a time with scripted
+//! keyboard input, for tests that play through the game.
+
+use std::time::Duration;
+
+use bevy::prelude::*;
+
+use crate::{
+    attract::{AttractDemo, AttractMode},
+    autopilot::{Autopilot, AutopilotStrategy},
+    env::{Action, GameEnv, Observation, STEP},
+    game_core::GameClock,
+    game_state::{GameState, GameStateType, OnGameStateChangedEvent},
+    ghost::{BestRun, Ghost, GhostSettings},
+    headless::headless_app,
+    mover::{Mover, MoverWindowLeftDespawnBound},
+    pillars::Difficulty,
+    player::{DeathCause, Player, PlayerCrossedPillarEvent, PlayerKilledEvent},
+    profile::Profile,
+    replay::ReplayMode,
+    score::{Score, ScoreUpdatedEvent},
+};
+
+/// Length of every tick, in seconds.
+pub const TICK: f32 = 1.0 / 60.0;
+
+/// The game without a window, renderer, sound or save files, on a clock that only moves when
+/// ticked.
+pub struct TestApp {
+    app: App,
+    /// keys held down during the next tick
+    pressed: Vec<KeyCode>,
+}
+
+/// Every event of a type sent since the app was built.
+struct EventLog<E>(Vec<E>);
+
+impl TestApp {
+    /// Builds the app and ticks it until the boot assets are loaded and the start screen is up.
+    pub fn new() -> Self {
+        let app = headless_app(|app| {
+            record::<OnGameStateChangedEvent>(app);
+            record::<ScoreUpdatedEvent>(app);
+            record::<PlayerCrossedPillarEvent>(app);
+            record::<PlayerKilledEvent>(app);
+        });
+
+        Self {
+            app,
+            pressed: vec![],
+        }
+    }
+
+    /// Holds a key down during the next tick, so it is `just_pressed` within that tick.
+    pub fn press(&mut self, key: KeyCode) -> &mut Self {
+        self.pressed.push(key);
+        self
+    }
+
+    /// Advances the clock by one tick and runs a frame with the keys pressed since the last one.
+    pub fn tick(&mut self) -> &mut Self {
+        let world = &mut self.app.world;
+
+        {
+            let mut input = world.get_resource_mut::<Input<KeyCode>>().unwrap();
+            input.clear();
+            self.pressed.iter().for_each(|key| input.press(*key));
+        }
+
+        world
+            .get_resource_mut::<GameClock>()
+            .unwrap()
+            .advance(Duration::from_secs_f32(TICK));
+
+        self.app.update();
+
+        let mut input = self.app.world.get_resource_mut::<Input<KeyCode>>().unwrap();
+        self.pressed.drain(..).for_each(|key| input.release(key));
+        self
+    }
+
+    /// Presses a key and runs the tick it is pressed in.
+    pub fn tap(&mut self, key: KeyCode) -> &mut Self {
+        self.press(key).tick()
+    }
+
+    /// Ticks until `done` returns true, for at most `seconds` of game time. `done` is called
+    /// before every tick and may press keys for it. Returns whether `done` was reached.
+    pub fn run_until(&mut self, seconds: f32, mut done: impl FnMut(&mut Self) -> bool) -> bool {
+        for _ in 0..(seconds / TICK).round() as u32 {
+            if done(self) {
+                return true;
+            }
+            self.tick();
+        }
+        done(self)
+    }
+
+    pub fn state(&self) -> GameStateType {
+        self.app.world.get_resource::<GameState>().unwrap().0
+    }
+
+    pub fn score(&self) -> u32 {
+        self.app.world.get_resource::<Score>().unwrap().0
+    }
+
+    /// Every event of the type sent so far, for the events recorded by `new`.
+    pub fn events<E: Send + Sync + 'static>(&self) -> &[E] {
+        &self
+            .app
+            .world
+            .get_resource::<EventLog<E>>()
+            .expect("events of this type are not recorded")
+            .0
+    }
+
+    pub fn world_mut(&mut self) -> &mut World {
+        &mut self.app.world
+    }
+}
+
+fn record<E: Clone + Send + Sync + 'static>(app: &mut App) {
+    app.insert_resource(EventLog::<E>(vec![]))
+        .add_system_to_stage(CoreStage::Last, record_events_system::<E>);
+}
+
+fn record_events_system<E: Clone + Send + Sync + 'static>(
+    mut log: ResMut<EventLog<E>>,
+    mut events: EventReader<E>,
+) {
+    log.0.extend(events.iter().cloned());
+}
+
+/// Pillars are out of the way once their centre is this far left of the player.
+const PILLAR_PASSED_X: f32 = -64.0;
+/// How far below the middle of the gap the player may sink before flapping.
+const FLAP_BELOW_GAP: f32 = 35.0;
+
+/// Flaps whenever the player sinks below the gap of the next pillar, or below the middle of
+/// the playfield while there is none.
+fn keep_in_gap(app: &mut TestApp) {
+    let world = app.world_mut();
+
+    let player_y = world
+        .query_filtered::<&Transform, With<Player>>()
+        .iter(world)
+        .next()
+        .unwrap()
+        .translation
+        .y;
+
+    let gap_y = world
+        .query_filtered::<(&Transform, &Mover), With<MoverWindowLeftDespawnBound>>()
+        .iter(world)
+        .filter(|(transform, mover)| mover.active && transform.translation.x > PILLAR_PASSED_X)
+        .min_by(|(a, _), (b, _)| a.translation.x.total_cmp(&b.translation.x))
+        .map_or(0.0, |(transform, _)| transform.translation.y);
+
+    if player_y < gap_y - FLAP_BELOW_GAP {
+        app.press(KeyCode::Space);
+    }
+}
+
+/// From the start screen through the get ready countdown, with a tap that is the first flap.
+fn start_playing(app: &mut TestApp) {
+    app.tap(KeyCode::Space);
+    assert_eq!(app.state(), GameStateType::GetReady);
+
+    app.tap(KeyCode::Space);
+    assert_eq!(app.state(), GameStateType::Playing);
+}
+
+#[test]
+fn boots_into_the_start_screen() {
+    let app = TestApp::new();
+
+    assert_eq!(app.state(), GameStateType::StartScreen);
+    assert_eq!(app.score(), 0);
+}
+
+#[test]
+fn countdown_begins_play_on_its_own() {
+    let mut app = TestApp::new();
+
+    app.tap(KeyCode::Space);
+    assert_eq!(app.state(), GameStateType::GetReady);
+
+    let playing = app.run_until(4.0, |app| app.state() == GameStateType::Playing);
+    assert!(playing, "the countdown never ended");
+}
+
+#[test]
+fn falling_to_the_floor_ends_the_run() {
+    let mut app = TestApp::new();
+    start_playing(&mut app);
+
+    let game_over = app.run_until(5.0, |app| app.state() == GameStateType::GameOver);
+    assert!(game_over, "the player never hit the floor");
+
+    let killed = app.events::<PlayerKilledEvent>();
+    assert_eq!(killed.len(), 1);
+    assert!(matches!(killed[0].cause, DeathCause::Floor));
+    assert_eq!(app.score(), 0);
+}
+
+fn best_score(app: &mut TestApp) -> u32 {
+    app.world_mut()
+        .get_resource::<Profile>()
+        .unwrap()
+        .best_score
+}
+
+#[test]
+fn plays_a_run_from_start_to_restart() {
+    let mut app = TestApp::new();
+    start_playing(&mut app);
+
+    let crossed = app.run_until(20.0, |app| {
+        keep_in_gap(app);
+        app.score() == 1
+    });
+    assert!(crossed, "the first pillar was never crossed");
+    assert_eq!(app.events::<PlayerCrossedPillarEvent>().len(), 1);
+    assert!(app
+        .events::<ScoreUpdatedEvent>()
+        .iter()
+        .any(|event| event.0 == 1));
+    // the best score is only saved once the run is over
+    assert_eq!(best_score(&mut app), 0);
+
+    // stop flapping and let the player fall
+    let game_over = app.run_until(5.0, |app| app.state() == GameStateType::GameOver);
+    assert!(game_over, "the run never ended");
+    assert_eq!(app.events::<PlayerKilledEvent>().len(), 1);
+    assert_eq!(app.score(), 1);
+    assert_eq!(best_score(&mut app), 1);
+
+    app.tap(KeyCode::R);
+    assert_eq!(app.state(), GameStateType::GetReady);
+    assert_eq!(app.score(), 0);
+
+    let states: Vec<GameStateType> = app
+        .events::<OnGameStateChangedEvent>()
+        .iter()
+        .map(|event| event.0)
+        .collect();
+    assert_eq!(
+        states,
+        [
+            GameStateType::StartScreen,
+            GameStateType::GetReady,
+            GameStateType::Playing,
+            GameStateType::GameOver,
+            GameStateType::GetReady,
+        ]
+    );
+}
+
+#[test]
+fn autopilot_flies_through_pillars() {
+    [AutopilotStrategy::Heuristic, AutopilotStrategy::Lookahead]
+        .into_iter()
+        .for_each(|strategy| {
+            let mut app = TestApp::new();
+            start_playing(&mut app);
+            app.world_mut().insert_resource(Autopilot {
+                enabled: true,
+                strategy,
+                restart: false,
+            });
+
+            let scored = app.run_until(20.0, |app| app.score() == 3);
+            assert!(scored, "{:?} did not get through three pillars", strategy);
+            assert!(app.events::<PlayerKilledEvent>().is_empty());
+        });
+}
+
+/// `keep_in_gap` for an agent of the environment.
+fn keep_in_gap_action(observation: &Observation) -> Action {
+    if observation.bird_y < observation.pillars[0].gap_y - FLAP_BELOW_GAP {
+        Action::Flap
+    } else {
+        Action::Idle
+    }
+}
+
+#[test]
+fn env_rewards_crossing_and_death() {
+    let mut env = GameEnv::new(Difficulty::Normal);
+    let mut observation = env.reset(Some(7));
+
+    let crossed = (0..(20.0 / STEP) as u32).any(|_| {
+        let (next, reward, done) = env.step(keep_in_gap_action(&observation));
+        assert!(!done, "the bird died on its way to the first pillar");
+        observation = next;
+        reward >= 1.0
+    });
+    assert!(crossed, "the first pillar was never crossed");
+
+    let died = (0..(5.0 / STEP) as u32).find_map(|_| {
+        let (_, reward, done) = env.step(Action::Idle);
+        done.then(|| reward)
+    });
+    let reward = died.expect("the bird never hit the floor");
+    assert!(reward < 0.0);
+
+    let (_, reward, done) = env.step(Action::Flap);
+    assert!(done);
+    assert_eq!(reward, 0.0);
+}
+
+#[test]
+fn env_runs_are_repeatable_from_a_seed() {
+    let mut env = GameEnv::new(Difficulty::Hard);
+
+    let play = |env: &mut GameEnv| {
+        let mut observation = env.reset(Some(42));
+        (0..(6.0 / STEP) as u32)
+            .map(|_| {
+                observation = env.step(keep_in_gap_action(&observation)).0;
+                observation
+            })
+            .collect::<Vec<_>>()
+    };
+
+    let first = play(&mut env);
+    let second = play(&mut env);
+    assert_eq!(first, second);
+}
+
+#[test]
+fn attract_mode_plays_a_demo_until_a_key_is_pressed() {
+    let mut app = TestApp::new();
+    app.world_mut().insert_resource(AttractMode {
+        idle_seconds: Some(1.0),
+        demo: AttractDemo::Autopilot(AutopilotStrategy::Heuristic),
+    });
+    let recorded = std::env::temp_dir().join("attract_demo_is_not_recorded.ron");
+    app.world_mut()
+        .insert_resource(ReplayMode::Record(recorded.clone()));
+
+    let demo = app.run_until(2.0, |app| app.state() == GameStateType::GetReady);
+    assert!(demo, "the demo never started");
+    assert!(matches!(
+        app.world_mut().get_resource::<ReplayMode>(),
+        Some(ReplayMode::Off)
+    ));
+
+    let playing = app.run_until(4.0, |app| app.state() == GameStateType::Playing);
+    assert!(playing, "the demo never left the countdown");
+
+    // the key ends the demo rather than flapping
+    app.tap(KeyCode::Space);
+    assert_eq!(app.state(), GameStateType::StartScreen);
+    assert_eq!(app.score(), 0);
+    assert!(matches!(
+        app.world_mut().get_resource::<ReplayMode>(),
+        Some(ReplayMode::Record(path)) if *path == recorded
+    ));
+}
+
+#[test]
+fn ghost_flies_the_best_run_alongside_the_player() {
+    let mut app = TestApp::new();
+    app.world_mut().insert_resource(GhostSettings {
+        enabled: true,
+        replay: None,
+    });
+
+    let play = |app: &mut TestApp| {
+        app.run_until(20.0, |app| {
+            keep_in_gap(app);
+            app.score() == 1
+        })
+    };
+
+    start_playing(&mut app);
+    assert!(play(&mut app), "the first pillar was never crossed");
+    let game_over = app.run_until(5.0, |app| app.state() == GameStateType::GameOver);
+    assert!(game_over, "the run never ended");
+
+    let best_score = app
+        .world_mut()
+        .get_resource::<BestRun>()
+        .unwrap()
+        .0
+        .as_ref()
+        .map(|replay| replay.score);
+    assert_eq!(best_score, Some(1));
+
+    // the same taps on the same pillars keep the player inside the ghost all the way
+    app.tap(KeyCode::R).tap(KeyCode::Space);
+    let (mut ghost_frames, mut crossed) = (0, false);
+    app.run_until(25.0, |app| {
+        if app.state() != GameStateType::Playing {
+            return true;
+        }
+
+        let world = app.world_mut();
+        let player_y = world
+            .query_filtered::<&Transform, With<Player>>()
+            .iter(world)
+            .next()
+            .unwrap()
+            .translation
+            .y;
+        let (ghost_transform, ghost_visibility) = world
+            .query_filtered::<(&Transform, &Visibility), With<Ghost>>()
+            .iter(world)
+            .next()
+            .unwrap();
+
+        assert!(ghost_visibility.is_visible);
+        assert_eq!(ghost_transform.translation.y, player_y);
+        ghost_frames += 1;
+
+        // flying as the first run did, up to the pillar and no further
+        if !crossed {
+            keep_in_gap(app);
+            crossed = app.score() == 1;
+        }
+        false
+    });
+
+    assert!(ghost_frames > 60, "the ghost did not fly the run");
+    assert_eq!(app.state(), GameStateType::GameOver);
+    assert_eq!(app.score(), 1);
+}
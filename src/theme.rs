@@ -0,0 +1,277 @@
+use bevy::{asset::AssetServerSettings, prelude::*};
+use serde::Deserialize;
+
+use crate::{
+    game_assets::{AssetBundle, GameAssets, GameAssetsSystem, LoadBundleEvent},
+    game_core::GameSystem,
+    loading::{LoadingAssets, PhaseFailedEvent, PhaseLoadedEvent, BOOT_PHASE},
+    profile::Profile,
+};
+
+pub struct ThemePlugin;
+
+impl Plugin for ThemePlugin {
+    fn build(&self, app: &mut App) {
+        app.insert_resource(Themes::default())
+            .add_event::<CycleThemeEvent>()
+            .add_startup_system_to_stage(
+                StartupStage::PreStartup,
+                load_themes
+                    .label(ThemeSystem::Load)
+                    .after(GameAssetsSystem::Manifest),
+            )
+            .add_system_set(
+                GameSystem::State
+                    .set()
+                    .with_system(cycle_theme_system)
+                    .with_system(theme_loaded_system),
+            )
+            .add_system_set(
+                GameSystem::Presentation
+                    .set()
+                    .with_system(apply_theme_system),
+            );
+    }
+}
+
+#[derive(SystemLabel, Debug, Clone, PartialEq, Eq, Hash)]
+pub enum ThemeSystem {
+    /// Startup system reading the theme files and picking the active theme
+    Load,
+}
+
+const THEME_FILE: &str = "theme.ron";
+
+/// Switches to the next theme that is unlocked.
+pub struct CycleThemeEvent;
+
+#[derive(Deserialize)]
+struct ThemeFile {
+    name: String,
+    #[serde(default)]
+    unlock_score: u32,
+    font: String,
+    palette: ThemePalette,
+    assets: AssetBundle,
+}
+
+#[derive(Deserialize, Clone, Copy)]
+pub struct ThemePalette {
+    text: (f32, f32, f32),
+    highlight: (f32, f32, f32),
+    clear: (f32, f32, f32),
+}
+
+impl ThemePalette {
+    pub fn color(&self, color: PaletteColor) -> Color {
+        let (r, g, b) = match color {
+            PaletteColor::Text => self.text,
+            PaletteColor::Highlight => self.highlight,
+        };
+        Color::rgb(r, g, b)
+    }
+}
+
+#[derive(Clone, Copy)]
+pub enum PaletteColor {
+    Text,
+    Highlight,
+}
+
+pub struct Theme {
+    pub id: String,
+    pub name: String,
+    pub unlock_score: u32,
+    pub font: String,
+    pub palette: ThemePalette,
+}
+
+impl Theme {
+    fn bundle(&self) -> String {
+        format!("theme:{}", self.id)
+    }
+
+    pub fn is_unlocked(&self, best_score: u32) -> bool {
+        best_score >= self.unlock_score
+    }
+}
+
+#[derive(Default)]
+pub struct Themes {
+    pub themes: Vec<Theme>,
+    active: usize,
+    /// theme whose bundle is being loaded, to become active once it is ready
+    pending: Option<usize>,
+}
+
+impl Themes {
+    pub fn active(&self) -> Option<&Theme> {
+        self.themes.get(self.active)
+    }
+
+    /// The lowest score that still has a locked theme waiting behind it.
+    pub fn next_unlock(&self, best_score: u32) -> Option<u32> {
+        self.themes
+            .iter()
+            .filter(|theme| !theme.is_unlocked(best_score))
+            .map(|theme| theme.unlock_score)
+            .min()
+    }
+}
+
+/// Sprites that take their texture from the active theme.
+#[derive(Component)]
+pub struct ThemedSprite(pub &'static str);
+
+/// Text whose sections are coloured from the active theme's palette, one colour per section.
+#[derive(Component)]
+pub struct ThemedText(pub Vec<PaletteColor>);
+
+fn load_themes(
+    asset_server: Res<AssetServer>,
+    settings: Option<Res<AssetServerSettings>>,
+    profile: Res<Profile>,
+    mut themes: ResMut<Themes>,
+    mut game_assets: ResMut<GameAssets>,
+    mut loading: ResMut<LoadingAssets>,
+) {
+    let theme_ids = game_assets.themes().to_vec();
+
+    theme_ids.into_iter().for_each(|id| {
+        let folder = format!("themes/{}", id);
+
+        match crate::game_assets::read_ron_asset::<ThemeFile>(
+            settings.as_deref(),
+            &format!("{}/{}", folder, THEME_FILE),
+        ) {
+            Ok(file) => {
+                let in_folder = |paths: std::collections::HashMap<String, String>| {
+                    paths
+                        .into_iter()
+                        .map(|(name, path)| (name, format!("{}/{}", folder, path)))
+                        .collect()
+                };
+
+                let theme = Theme {
+                    id,
+                    name: file.name,
+                    unlock_score: file.unlock_score,
+                    font: file.font,
+                    palette: file.palette,
+                };
+
+                game_assets.add_bundle(
+                    &theme.bundle(),
+                    AssetBundle {
+                        textures: in_folder(file.assets.textures),
+                        fonts: in_folder(file.assets.fonts),
+                        sounds: in_folder(file.assets.sounds),
+                    },
+                );
+                themes.themes.push(theme);
+            }
+            Err(err) => {
+                let _ = loading.fail(BOOT_PHASE, format!("theme \"{}\": {}", id, err));
+            }
+        }
+    });
+
+    // fall back to the first theme if the saved one is gone or no longer unlocked
+    themes.active = themes
+        .themes
+        .iter()
+        .position(|theme| theme.id == profile.theme && theme.is_unlocked(profile.best_score))
+        .unwrap_or(0);
+
+    if let Some(theme) = themes.active() {
+        let bundle = theme.bundle();
+
+        if let Err(err) = game_assets.load_bundle(&bundle, BOOT_PHASE, &asset_server, &mut loading)
+        {
+            let _ = loading.fail(BOOT_PHASE, err);
+        }
+    }
+}
+
+fn cycle_theme_system(
+    profile: Res<Profile>,
+    mut themes: ResMut<Themes>,
+    mut cycle_events: EventReader<CycleThemeEvent>,
+    mut load_bundle_events: EventWriter<LoadBundleEvent>,
+) {
+    if cycle_events.iter().count() > 0 && themes.pending.is_none() {
+        let count = themes.themes.len();
+
+        let next = (1..count)
+            .map(|offset| (themes.active + offset) % count)
+            .find(|index| themes.themes[*index].is_unlocked(profile.best_score));
+
+        if let Some(next) = next {
+            themes.pending = Some(next);
+            load_bundle_events.send(LoadBundleEvent(themes.themes[next].bundle()));
+        }
+    }
+}
+
+fn theme_loaded_system(
+    mut profile: ResMut<Profile>,
+    mut themes: ResMut<Themes>,
+    mut phase_loaded_events: EventReader<PhaseLoadedEvent>,
+    mut phase_failed_events: EventReader<PhaseFailedEvent>,
+) {
+    phase_loaded_events.iter().for_each(|event| {
+        if let Some(pending) = themes.pending {
+            if event.0 == themes.themes[pending].bundle() {
+                themes.active = pending;
+                themes.pending = None;
+                profile.theme = themes.themes[pending].id.clone();
+            }
+        }
+    });
+
+    // keep the current theme, and let the player cycle again
+    phase_failed_events.iter().for_each(|event| {
+        if let Some(pending) = themes.pending {
+            if event.0 == themes.themes[pending].bundle() {
+                themes.pending = None;
+            }
+        }
+    });
+}
+
+/// Points the themed sprites and text at the assets of the active theme whenever it changes,
+/// but not when a theme only starts or stops loading.
+fn apply_theme_system(
+    mut applied: Local<Option<usize>>,
+    themes: Res<Themes>,
+    game_assets: Res<GameAssets>,
+    mut clear_color: ResMut<ClearColor>,
+    mut sprite_query: Query<(&ThemedSprite, &mut Handle<Image>)>,
+    mut text_query: Query<(&ThemedText, &mut Text)>,
+) {
+    if !themes.is_changed() || *applied == Some(themes.active) {
+        return;
+    }
+    *applied = Some(themes.active);
+
+    if let Some(theme) = themes.active() {
+        let (r, g, b) = theme.palette.clear;
+        clear_color.0 = Color::rgb(r, g, b);
+
+        sprite_query.iter_mut().for_each(|(themed, mut texture)| {
+            *texture = game_assets.texture(themed.0);
+        });
+
+        let font = game_assets.font(&theme.font);
+
+        text_query.iter_mut().for_each(|(themed, mut text)| {
+            text.sections
+                .iter_mut()
+                .zip(themed.0.iter())
+                .for_each(|(section, color)| {
+                    section.style.font = font.clone();
+                    section.style.color = theme.palette.color(*color);
+                });
+        });
+    }
+}
@@ -0,0 +1,340 @@
+use bevy::{ecs::system::EntityCommands, input::InputSystem, prelude::*, ui::UiSystem};
+
+use crate::{
+    game_assets::GameAssets,
+    game_core::GameSystem,
+    locale::LocalizedText,
+    theme::{PaletteColor, ThemeSystem, ThemedText, Themes},
+};
+
+pub struct WidgetsPlugin;
+
+impl Plugin for WidgetsPlugin {
+    fn build(&self, app: &mut App) {
+        app.insert_resource(UiTheme::default())
+            .add_event::<ButtonClickedEvent>()
+            .add_startup_system_to_stage(
+                StartupStage::PreStartup,
+                ui_theme_system.after(ThemeSystem::Load),
+            )
+            // clicks are turned into events before any phase of the game reads them
+            .add_system_to_stage(
+                CoreStage::PreUpdate,
+                button_interaction_system.after(UiSystem::Focus),
+            )
+            .add_system_to_stage(
+                CoreStage::PreUpdate,
+                menu_navigation_system.after(InputSystem),
+            )
+            .add_system_set(
+                GameSystem::Presentation
+                    .set()
+                    .with_system(ui_theme_system)
+                    .with_system(button_style_system),
+            );
+    }
+}
+
+/// Fonts, colours and sizes shared by every widget, following the active theme.
+pub struct UiTheme {
+    pub font: Handle<Font>,
+    pub text_color: Color,
+    pub highlight_color: Color,
+    pub title_size: f32,
+    pub label_size: f32,
+    pub button_size: f32,
+}
+
+impl Default for UiTheme {
+    fn default() -> Self {
+        Self {
+            font: Handle::default(),
+            text_color: Color::BLACK,
+            highlight_color: Color::RED,
+            title_size: 60.0,
+            label_size: 30.0,
+            button_size: 40.0,
+        }
+    }
+}
+
+impl UiTheme {
+    fn button_color(&self, state: ButtonState) -> Color {
+        match state {
+            ButtonState::Normal => self.text_color.clone().set_a(0.1).as_rgba(),
+            ButtonState::Focused => self.text_color.clone().set_a(0.3).as_rgba(),
+            ButtonState::Pressed => self.highlight_color.clone().set_a(0.5).as_rgba(),
+        }
+    }
+
+    fn text_style(&self, font_size: f32, color: PaletteColor) -> TextStyle {
+        TextStyle {
+            font: self.font.clone(),
+            font_size,
+            color: match color {
+                PaletteColor::Text => self.text_color,
+                PaletteColor::Highlight => self.highlight_color,
+            },
+        }
+    }
+}
+
+enum ButtonState {
+    Normal,
+    Focused,
+    Pressed,
+}
+
+/// Sent when a button is clicked with the mouse, or activated from the keyboard while focused.
+pub struct ButtonClickedEvent(pub Entity);
+
+#[derive(Component, Default)]
+pub struct WidgetButton {
+    /// the mouse went down over this button and has not been released yet
+    pressed: bool,
+}
+
+/// The text inside of a button.
+#[derive(Component)]
+pub struct ButtonLabel;
+
+/// A vertical list of buttons, of which one has keyboard focus.
+#[derive(Component, Default)]
+pub struct Menu {
+    pub focused: usize,
+}
+
+const CENTERED: TextAlignment = TextAlignment {
+    vertical: VerticalAlign::Center,
+    horizontal: HorizontalAlign::Center,
+};
+
+/// A full window, transparent node laying out its children from the top down, centered.
+pub fn screen<'w, 's, 'a>(commands: &'a mut Commands<'w, 's>) -> EntityCommands<'w, 's, 'a> {
+    commands.spawn_bundle(NodeBundle {
+        style: Style {
+            position_type: PositionType::Absolute,
+            size: Size::new(Val::Percent(100.0), Val::Percent(100.0)),
+            // bevy ui is y-up, so reverse the column to lay children out top to bottom
+            flex_direction: FlexDirection::ColumnReverse,
+            justify_content: JustifyContent::Center,
+            align_items: AlignItems::Center,
+            ..Default::default()
+        },
+        color: UiColor(Color::NONE),
+        ..Default::default()
+    })
+}
+
+pub fn title<'w, 's, 'a>(
+    parent: &'a mut ChildBuilder<'w, 's, '_>,
+    ui_theme: &UiTheme,
+    value: impl Into<LocalizedText>,
+) -> EntityCommands<'w, 's, 'a> {
+    text(
+        parent,
+        ui_theme,
+        value,
+        ui_theme.title_size,
+        PaletteColor::Text,
+    )
+}
+
+pub fn label<'w, 's, 'a>(
+    parent: &'a mut ChildBuilder<'w, 's, '_>,
+    ui_theme: &UiTheme,
+    value: impl Into<LocalizedText>,
+) -> EntityCommands<'w, 's, 'a> {
+    text(
+        parent,
+        ui_theme,
+        value,
+        ui_theme.label_size,
+        PaletteColor::Text,
+    )
+}
+
+pub fn text<'w, 's, 'a>(
+    parent: &'a mut ChildBuilder<'w, 's, '_>,
+    ui_theme: &UiTheme,
+    value: impl Into<LocalizedText>,
+    font_size: f32,
+    color: PaletteColor,
+) -> EntityCommands<'w, 's, 'a> {
+    let mut entity = parent.spawn_bundle(TextBundle {
+        // filled in from the string tables once spawned
+        text: Text::with_section("", ui_theme.text_style(font_size, color), CENTERED),
+        style: Style {
+            margin: Rect::all(Val::Px(8.0)),
+            ..Default::default()
+        },
+        ..Default::default()
+    });
+    let localized: LocalizedText = value.into();
+    entity.insert(ThemedText(vec![color])).insert(localized);
+    entity
+}
+
+pub fn menu<'w, 's, 'a>(parent: &'a mut ChildBuilder<'w, 's, '_>) -> EntityCommands<'w, 's, 'a> {
+    let mut entity = parent.spawn_bundle(NodeBundle {
+        style: Style {
+            flex_direction: FlexDirection::ColumnReverse,
+            align_items: AlignItems::Stretch,
+            margin: Rect::all(Val::Px(16.0)),
+            ..Default::default()
+        },
+        color: UiColor(Color::NONE),
+        ..Default::default()
+    });
+    entity.insert(Menu::default());
+    entity
+}
+
+pub fn button<'w, 's, 'a>(
+    parent: &'a mut ChildBuilder<'w, 's, '_>,
+    ui_theme: &UiTheme,
+    value: impl Into<LocalizedText>,
+) -> EntityCommands<'w, 's, 'a> {
+    let mut entity = parent.spawn_bundle(ButtonBundle {
+        style: Style {
+            min_size: Size::new(Val::Px(280.0), Val::Px(ui_theme.button_size + 24.0)),
+            margin: Rect::all(Val::Px(6.0)),
+            padding: Rect::all(Val::Px(8.0)),
+            justify_content: JustifyContent::Center,
+            align_items: AlignItems::Center,
+            ..Default::default()
+        },
+        color: UiColor(ui_theme.button_color(ButtonState::Normal)),
+        ..Default::default()
+    });
+
+    let localized: LocalizedText = value.into();
+    entity
+        .insert(WidgetButton::default())
+        .with_children(|parent| {
+            parent
+                .spawn_bundle(TextBundle {
+                    text: Text::with_section(
+                        "",
+                        ui_theme.text_style(ui_theme.button_size, PaletteColor::Text),
+                        CENTERED,
+                    ),
+                    ..Default::default()
+                })
+                .insert(ThemedText(vec![PaletteColor::Text]))
+                .insert(localized)
+                .insert(ButtonLabel);
+        });
+    entity
+}
+
+fn ui_theme_system(
+    themes: Res<Themes>,
+    game_assets: Res<GameAssets>,
+    mut ui_theme: ResMut<UiTheme>,
+) {
+    if themes.is_changed() {
+        if let Some(theme) = themes.active() {
+            ui_theme.font = game_assets.font(&theme.font);
+            ui_theme.text_color = theme.palette.color(PaletteColor::Text);
+            ui_theme.highlight_color = theme.palette.color(PaletteColor::Highlight);
+        }
+    }
+}
+
+fn button_interaction_system(
+    mut query: Query<
+        (Entity, &Interaction, &mut WidgetButton, Option<&Parent>),
+        Changed<Interaction>,
+    >,
+    mut menu_query: Query<(&mut Menu, &Children)>,
+    mut clicked_events: EventWriter<ButtonClickedEvent>,
+) {
+    query.iter_mut().for_each(
+        |(entity, interaction, mut button, parent)| match interaction {
+            Interaction::Clicked => button.pressed = true,
+            Interaction::Hovered => {
+                // released while still over the button
+                if button.pressed {
+                    clicked_events.send(ButtonClickedEvent(entity));
+                }
+                button.pressed = false;
+
+                // the mouse moves keyboard focus along with it
+                if let Some(Ok((mut menu, children))) =
+                    parent.map(|parent| menu_query.get_mut(parent.0))
+                {
+                    if let Some(index) = children.iter().position(|child| *child == entity) {
+                        menu.focused = index;
+                    }
+                }
+            }
+            Interaction::None => button.pressed = false,
+        },
+    );
+}
+
+fn menu_navigation_system(
+    keyboard_input: Res<Input<KeyCode>>,
+    gamepad_input: Res<Input<GamepadButton>>,
+    mut menu_query: Query<(&mut Menu, &Children)>,
+    mut clicked_events: EventWriter<ButtonClickedEvent>,
+) {
+    // any connected gamepad can drive the menus
+    let gamepad_pressed = |button_type: GamepadButtonType| {
+        gamepad_input
+            .get_just_pressed()
+            .any(|button| button.1 == button_type)
+    };
+
+    let up = keyboard_input.any_just_pressed([KeyCode::Up, KeyCode::W])
+        || gamepad_pressed(GamepadButtonType::DPadUp);
+    let down = keyboard_input.any_just_pressed([KeyCode::Down, KeyCode::S])
+        || gamepad_pressed(GamepadButtonType::DPadDown);
+    let activate = keyboard_input.any_just_pressed([KeyCode::Return, KeyCode::Space])
+        || gamepad_pressed(GamepadButtonType::South);
+
+    menu_query.iter_mut().for_each(|(mut menu, children)| {
+        let count = children.len();
+        if count == 0 {
+            return;
+        }
+
+        if up {
+            menu.focused = (menu.focused + count - 1) % count;
+        } else if down {
+            menu.focused = (menu.focused + 1) % count;
+        } else if activate {
+            if let Some(focused) = children.get(menu.focused) {
+                clicked_events.send(ButtonClickedEvent(*focused));
+            }
+        }
+    });
+}
+
+fn button_style_system(
+    ui_theme: Res<UiTheme>,
+    menu_query: Query<(&Menu, &Children)>,
+    mut button_query: Query<(Entity, &Interaction, &mut UiColor), With<WidgetButton>>,
+) {
+    let focused: Vec<Entity> = menu_query
+        .iter()
+        .filter_map(|(menu, children)| children.get(menu.focused).copied())
+        .collect();
+
+    button_query
+        .iter_mut()
+        .for_each(|(entity, interaction, mut color)| {
+            let state = match interaction {
+                Interaction::Clicked => ButtonState::Pressed,
+                _ if focused.contains(&entity) => ButtonState::Focused,
+                Interaction::Hovered => ButtonState::Focused,
+                Interaction::None => ButtonState::Normal,
+            };
+
+            let new_color = ui_theme.button_color(state);
+            if color.0 != new_color {
+                color.0 = new_color;
+            }
+        });
+}